@@ -0,0 +1,65 @@
+use bevy::asset::AssetMetaCheck;
+use bevy::core_pipeline::bloom::BloomSettings;
+use bevy::prelude::*;
+use bevy_vello::vello::{kurbo, peniko};
+use bevy_vello::{prelude::*, VelloPlugin};
+
+fn main() {
+    App::new()
+        .insert_resource(AssetMetaCheck::Never)
+        .add_plugins(DefaultPlugins)
+        .add_plugins(VelloPlugin)
+        .add_systems(Startup, setup)
+        .run()
+}
+
+fn setup(mut commands: Commands) {
+    // The canvases are composited into the HDR target, before bloom and tonemapping.
+    commands.spawn((
+        Camera2dBundle {
+            camera: Camera {
+                hdr: true,
+                ..default()
+            },
+            ..default()
+        },
+        BloomSettings::default(),
+    ));
+
+    commands.spawn(VelloSceneBundle {
+        scene: ring(peniko::Color::rgb(0.2, 0.4, 1.0), 150.0),
+        ..default()
+    });
+
+    // Exposure takes this layer above 1.0, so it blooms.
+    commands
+        .spawn((
+            SpatialBundle::default(),
+            VelloLayer,
+            VelloPostProcessing {
+                color_grading: VelloColorGrading {
+                    exposure: 2.0,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(VelloSceneBundle {
+                scene: ring(peniko::Color::rgb(1.0, 0.4, 0.2), 75.0),
+                ..default()
+            });
+        });
+}
+
+fn ring(color: peniko::Color, radius: f64) -> VelloScene {
+    let mut scene = VelloScene::default();
+    scene.stroke(
+        &kurbo::Stroke::new(12.0),
+        kurbo::Affine::default(),
+        color,
+        None,
+        &kurbo::Circle::new((0.0, 0.0), radius),
+    );
+    scene
+}
@@ -0,0 +1,127 @@
+use bevy::app::{AppExit, ScheduleRunnerPlugin};
+use bevy::prelude::*;
+use bevy::render::camera::RenderTarget;
+use bevy::render::render_resource::{
+    Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+};
+use bevy::render::RenderPlugin;
+use bevy::window::ExitCondition;
+use bevy::winit::WinitPlugin;
+use bevy_vello::render::fallback_adapter_render_creation;
+use bevy_vello::vello::{kurbo, peniko};
+use bevy_vello::{prelude::*, VelloPlugin};
+use std::time::Duration;
+
+fn main() {
+    // Pass `--software` to render on wgpu's fallback adapter, e.g. lavapipe,
+    // on machines without a GPU.
+    let software = std::env::args().any(|arg| arg == "--software");
+
+    let mut default_plugins = DefaultPlugins
+        .set(WindowPlugin {
+            primary_window: None,
+            exit_condition: ExitCondition::DontExit,
+            ..default()
+        })
+        .disable::<WinitPlugin>();
+    if software {
+        let render_creation = fallback_adapter_render_creation(default())
+            .expect("No fallback adapter found, install a software rasterizer such as lavapipe");
+        default_plugins = default_plugins.set(RenderPlugin {
+            render_creation,
+            ..default()
+        });
+    }
+
+    App::new()
+        .add_plugins(default_plugins)
+        // Without winit, something else has to drive the app.
+        .add_plugins(ScheduleRunnerPlugin::run_loop(Duration::from_secs_f64(
+            1.0 / 60.0,
+        )))
+        // Software adapters may not run Vello's compute shaders, use its CPU shaders there.
+        .insert_resource(VelloRenderSettings {
+            use_cpu: software,
+            ..default()
+        })
+        .add_plugins(VelloPlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, save_frame)
+        .run()
+}
+
+fn setup(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let size = Extent3d {
+        width: 256,
+        height: 256,
+        ..default()
+    };
+
+    // The image must be copyable to be read back.
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: None,
+            size,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Rgba8UnormSrgb,
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::COPY_SRC
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..default()
+    };
+    image.resize(size);
+    let image_handle = images.add(image);
+
+    commands.spawn(Camera2dBundle {
+        camera: Camera {
+            target: RenderTarget::Image(image_handle.clone()),
+            clear_color: ClearColorConfig::Custom(Color::WHITE),
+            ..default()
+        },
+        ..default()
+    });
+    commands.spawn(ImageReadback::new(image_handle));
+
+    let mut scene = VelloScene::default();
+    scene.fill(
+        peniko::Fill::NonZero,
+        kurbo::Affine::default(),
+        peniko::Color::rgb(0.2, 0.4, 1.0),
+        None,
+        &kurbo::RoundedRect::new(-50.0, -50.0, 50.0, 50.0, 20.0),
+    );
+    commands.spawn(VelloSceneBundle { scene, ..default() });
+}
+
+fn save_frame(
+    readback: Query<&ImageReadback>,
+    mut frames: Local<u32>,
+    mut exit: EventWriter<AppExit>,
+) {
+    // Give the pipelines a few frames to compile before reading back.
+    *frames += 1;
+    if *frames < 10 {
+        return;
+    }
+    let Some(frame) = readback.single().latest() else {
+        return;
+    };
+
+    // A binary PPM needs no image encoder, and most image viewers can open it.
+    let size = frame.size();
+    let mut ppm = format!("P6\n{} {}\n255\n", size.x, size.y).into_bytes();
+    for pixel in frame.data.chunks(4) {
+        ppm.extend_from_slice(&pixel[..3]);
+    }
+    let path = "headless.ppm";
+    match std::fs::write(path, ppm) {
+        Ok(()) => info!("Wrote {path}"),
+        Err(err) => error!("Could not write {path}: {err}"),
+    }
+    exit.send(AppExit);
+}
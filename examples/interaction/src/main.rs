@@ -0,0 +1,100 @@
+use bevy::asset::AssetMetaCheck;
+use bevy::prelude::*;
+use bevy_vello::vello::peniko;
+use bevy_vello::{prelude::*, VelloPlugin};
+
+fn main() {
+    App::new()
+        .insert_resource(AssetMetaCheck::Never)
+        .add_plugins(DefaultPlugins)
+        .add_plugins(VelloPlugin)
+        .add_systems(Startup, setup_menu)
+        .add_systems(Update, (highlight_hovered, handle_pointer_events))
+        .run()
+}
+
+#[derive(Component)]
+struct MenuItem(&'static str);
+
+#[derive(Component)]
+struct Draggable;
+
+const IDLE: peniko::Color = peniko::Color::rgb8(0x41, 0xb3, 0xa3);
+const HOVERED: peniko::Color = peniko::Color::rgb8(0x6c, 0xd4, 0xc5);
+const PRESSED: peniko::Color = peniko::Color::rgb8(0x2a, 0x80, 0x74);
+
+fn setup_menu(mut commands: Commands) {
+    commands.spawn(Camera2dBundle::default());
+
+    // A menu pinned to the top left of the window
+    for (i, name) in ["play", "options", "quit"].into_iter().enumerate() {
+        commands.spawn((
+            VelloShapeBundle {
+                shape: VelloShape::rounded_rect(160.0, 40.0, 8.0),
+                alignment: VelloAssetAlignment::TopLeft,
+                coordinate_space: CoordinateSpace::ScreenSpace,
+                transform: Transform::from_xyz(20.0, 20.0 + i as f32 * 50.0, 0.0),
+                ..default()
+            },
+            VelloFill::new(IDLE),
+            VelloInteraction::default(),
+            MenuItem(name),
+        ));
+    }
+
+    // A card in the world that can be dragged around
+    commands.spawn((
+        VelloShapeBundle {
+            shape: VelloShape::rounded_rect(200.0, 120.0, 16.0),
+            ..default()
+        },
+        VelloFill::new(peniko::Color::rgb8(0xe2, 0x7d, 0x60)),
+        VelloInteraction::default(),
+        Draggable,
+    ));
+}
+
+fn highlight_hovered(mut items: Query<(Ref<VelloInteraction>, &mut VelloFill), With<MenuItem>>) {
+    for (interaction, mut fill) in items.iter_mut() {
+        if !interaction.is_changed() {
+            continue;
+        }
+        let color = if interaction.is_pressed(MouseButton::Left) {
+            PRESSED
+        } else if interaction.is_hovered() {
+            HOVERED
+        } else {
+            IDLE
+        };
+        fill.brush = color.into();
+    }
+}
+
+fn handle_pointer_events(
+    mut events: EventReader<VelloPointerEvent>,
+    menu_items: Query<&MenuItem>,
+    mut draggables: Query<&mut Transform, With<Draggable>>,
+) {
+    for event in events.read() {
+        match *event {
+            VelloPointerEvent::Click {
+                entity,
+                button: MouseButton::Left,
+            } => {
+                if let Ok(item) = menu_items.get(entity) {
+                    info!("selected {}", item.0);
+                }
+            }
+            VelloPointerEvent::Drag {
+                entity,
+                button: MouseButton::Left,
+                delta,
+            } => {
+                if let Ok(mut transform) = draggables.get_mut(entity) {
+                    transform.translation += delta.extend(0.0);
+                }
+            }
+            _ => {}
+        }
+    }
+}
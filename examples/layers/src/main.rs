@@ -0,0 +1,66 @@
+use bevy::asset::AssetMetaCheck;
+use bevy::prelude::*;
+use bevy_vello::vello::{kurbo, peniko};
+use bevy_vello::{prelude::*, VelloPlugin};
+
+fn main() {
+    App::new()
+        .insert_resource(AssetMetaCheck::Never)
+        .add_plugins(DefaultPlugins)
+        .add_plugins(VelloPlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, move_sprite)
+        .run()
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn(Camera2dBundle::default());
+
+    // Content outside of any layer is drawn on the camera's own canvas.
+    commands.spawn(VelloSceneBundle {
+        scene: circle(peniko::Color::rgb(0.2, 0.4, 1.0), 150.0),
+        ..default()
+    });
+
+    // The sprite sits above the canvas...
+    commands.spawn(SpriteBundle {
+        sprite: Sprite {
+            color: Color::ORANGE_RED,
+            custom_size: Some(Vec2::new(100.0, 400.0)),
+            ..default()
+        },
+        transform: Transform::from_xyz(0.0, 0.0, 1.0),
+        ..default()
+    });
+
+    // ...and below this layer, which is drawn as its own mesh at z = 2.
+    commands
+        .spawn((
+            SpatialBundle::from_transform(Transform::from_xyz(0.0, 0.0, 2.0)),
+            VelloLayer,
+        ))
+        .with_children(|parent| {
+            parent.spawn(VelloSceneBundle {
+                scene: circle(peniko::Color::rgb(0.2, 0.8, 0.4), 75.0),
+                ..default()
+            });
+        });
+}
+
+fn circle(color: peniko::Color, radius: f64) -> VelloScene {
+    let mut scene = VelloScene::default();
+    scene.fill(
+        peniko::Fill::NonZero,
+        kurbo::Affine::default(),
+        color,
+        None,
+        &kurbo::Circle::new((0.0, 0.0), radius),
+    );
+    scene
+}
+
+fn move_sprite(mut query: Query<&mut Transform, With<Sprite>>, time: Res<Time>) {
+    for mut transform in query.iter_mut() {
+        transform.translation.x = time.elapsed_seconds().sin() * 200.0;
+    }
+}
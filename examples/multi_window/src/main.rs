@@ -0,0 +1,60 @@
+use bevy::asset::AssetMetaCheck;
+use bevy::prelude::*;
+use bevy::render::camera::RenderTarget;
+use bevy::window::WindowRef;
+use bevy_vello::vello::{kurbo, peniko};
+use bevy_vello::{prelude::*, VelloPlugin};
+
+fn main() {
+    App::new()
+        .insert_resource(AssetMetaCheck::Never)
+        .add_plugins(DefaultPlugins)
+        .add_plugins(VelloPlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, simple_animation)
+        .run()
+}
+
+fn setup(mut commands: Commands) {
+    // A camera for the primary window
+    commands.spawn(Camera2dBundle::default());
+
+    // A second window, with its own camera
+    let second_window = commands
+        .spawn(Window {
+            title: "Second window".to_owned(),
+            ..default()
+        })
+        .id();
+    commands.spawn(Camera2dBundle {
+        camera: Camera {
+            target: RenderTarget::Window(WindowRef::Entity(second_window)),
+            ..default()
+        },
+        projection: OrthographicProjection {
+            scale: 0.5,
+            ..default()
+        },
+        ..default()
+    });
+
+    commands.spawn(VelloSceneBundle::default());
+}
+
+fn simple_animation(mut query_scene: Query<(&mut Transform, &mut VelloScene)>, time: Res<Time>) {
+    let sin_time = time.elapsed_seconds().sin().mul_add(0.5, 0.5);
+    let (mut transform, mut scene) = query_scene.single_mut();
+
+    // Reset scene every frame
+    *scene = VelloScene::default();
+
+    scene.fill(
+        peniko::Fill::NonZero,
+        kurbo::Affine::default(),
+        peniko::Color::rgb(1.0, 0.4, 0.2),
+        None,
+        &kurbo::RoundedRect::new(-50.0, -50.0, 50.0, 50.0, (sin_time as f64) * 50.0),
+    );
+
+    transform.rotation = Quat::from_rotation_z(-std::f32::consts::TAU * sin_time);
+}
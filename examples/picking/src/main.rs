@@ -0,0 +1,94 @@
+use bevy::asset::AssetMetaCheck;
+use bevy::prelude::*;
+use bevy_vello::vello::kurbo::{self, Shape};
+use bevy_vello::vello::peniko;
+use bevy_vello::{prelude::*, VelloPlugin};
+
+fn main() {
+    App::new()
+        .insert_resource(AssetMetaCheck::Never)
+        .add_plugins(DefaultPlugins)
+        .add_plugins(VelloPlugin)
+        .add_systems(Startup, setup_buttons)
+        .add_systems(Update, (highlight_hovered, log_clicks))
+        .run()
+}
+
+#[derive(Component)]
+struct Colors {
+    idle: peniko::Color,
+    hovered: peniko::Color,
+}
+
+fn setup_buttons(mut commands: Commands) {
+    commands.spawn(Camera2dBundle::default());
+
+    // Clicks in the hole of the donut go to whatever is behind it
+    let mut donut = kurbo::Circle::new((0.0, 0.0), 120.0).to_path(0.1);
+    donut.extend(kurbo::Circle::new((0.0, 0.0), 60.0).to_path(0.1));
+    let colors = Colors {
+        idle: peniko::Color::rgb8(0xe2, 0x7d, 0x60),
+        hovered: peniko::Color::rgb8(0xf0, 0xa0, 0x88),
+    };
+    commands.spawn((
+        VelloShapeBundle {
+            shape: VelloShape::Path(donut),
+            transform: Transform::from_xyz(0.0, 0.0, 1.0),
+            ..default()
+        },
+        VelloFill::new(colors.idle).with_rule(peniko::Fill::EvenOdd),
+        VelloPickable,
+        colors,
+        Name::new("donut"),
+    ));
+
+    let colors = Colors {
+        idle: peniko::Color::rgb8(0x41, 0xb3, 0xa3),
+        hovered: peniko::Color::rgb8(0x6c, 0xd4, 0xc5),
+    };
+    commands.spawn((
+        VelloShapeBundle {
+            shape: VelloShape::rounded_rect(400.0, 80.0, 12.0),
+            ..default()
+        },
+        VelloFill::new(colors.idle),
+        VelloPickable,
+        colors,
+        Name::new("bar"),
+    ));
+}
+
+/// Lighten the top-most button under the cursor.
+fn highlight_hovered(
+    picking: Res<VelloPicking>,
+    mut buttons: Query<(Entity, &Colors, &mut VelloFill)>,
+) {
+    if !picking.is_changed() {
+        return;
+    }
+    for (entity, colors, mut fill) in buttons.iter_mut() {
+        let color = if picking.topmost_hit() == Some(entity) {
+            colors.hovered
+        } else {
+            colors.idle
+        };
+        fill.brush = color.into();
+    }
+}
+
+fn log_clicks(
+    picking: Res<VelloPicking>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    names: Query<&Name>,
+) {
+    if !buttons.just_pressed(MouseButton::Left) {
+        return;
+    }
+    match picking
+        .topmost_hit()
+        .and_then(|entity| names.get(entity).ok())
+    {
+        Some(name) => info!("clicked the {name}"),
+        None => info!("clicked nothing"),
+    }
+}
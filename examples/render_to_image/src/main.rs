@@ -0,0 +1,96 @@
+use bevy::asset::AssetMetaCheck;
+use bevy::prelude::*;
+use bevy::render::camera::RenderTarget;
+use bevy::render::render_resource::{
+    Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+};
+use bevy::render::view::RenderLayers;
+use bevy_vello::vello::{kurbo, peniko};
+use bevy_vello::{prelude::*, VelloPlugin};
+
+fn main() {
+    App::new()
+        .insert_resource(AssetMetaCheck::Never)
+        .add_plugins(DefaultPlugins)
+        .add_plugins(VelloPlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, simple_animation)
+        .run()
+}
+
+fn setup(mut commands: Commands, mut images: ResMut<Assets<Image>>) {
+    let size = Extent3d {
+        width: 512,
+        height: 512,
+        ..default()
+    };
+
+    // This is the texture the offscreen camera will render to.
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: None,
+            size,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Bgra8UnormSrgb,
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..default()
+    };
+    image.resize(size);
+    let image_handle = images.add(image);
+
+    // The offscreen camera only sees the Vello scene, and renders it into the image.
+    commands.spawn(Camera2dBundle {
+        camera: Camera {
+            target: RenderTarget::Image(image_handle.clone()),
+            order: -1,
+            clear_color: ClearColorConfig::Custom(Color::WHITE),
+            ..default()
+        },
+        projection: OrthographicProjection {
+            scale: 0.5,
+            ..default()
+        },
+        ..default()
+    });
+
+    // The main camera renders the Vello scene, and the image on a sprite.
+    commands.spawn((
+        Camera2dBundle::default(),
+        RenderLayers::from_layers(&[0, 1]),
+    ));
+    commands.spawn((
+        SpriteBundle {
+            texture: image_handle,
+            transform: Transform::from_xyz(0.0, -150.0, 0.0).with_scale(Vec3::splat(0.5)),
+            ..default()
+        },
+        RenderLayers::layer(1),
+    ));
+
+    commands.spawn(VelloSceneBundle::default());
+}
+
+fn simple_animation(mut query_scene: Query<(&mut Transform, &mut VelloScene)>, time: Res<Time>) {
+    let sin_time = time.elapsed_seconds().sin().mul_add(0.5, 0.5);
+    let (mut transform, mut scene) = query_scene.single_mut();
+
+    // Reset scene every frame
+    *scene = VelloScene::default();
+
+    scene.fill(
+        peniko::Fill::NonZero,
+        kurbo::Affine::default(),
+        peniko::Color::rgb(0.2, 0.4, 1.0),
+        None,
+        &kurbo::RoundedRect::new(-50.0, -50.0, 50.0, 50.0, (sin_time as f64) * 50.0),
+    );
+
+    transform.translation = Vec3::new(0.0, 150.0, 0.0);
+    transform.rotation = Quat::from_rotation_z(-std::f32::consts::TAU * sin_time);
+}
@@ -0,0 +1,77 @@
+use bevy::asset::AssetMetaCheck;
+use bevy::prelude::*;
+use bevy_vello::vello::{kurbo, peniko};
+use bevy_vello::{prelude::*, VelloPlugin};
+
+fn main() {
+    App::new()
+        .insert_resource(AssetMetaCheck::Never)
+        .add_plugins(DefaultPlugins)
+        .add_plugins(VelloPlugin)
+        .add_systems(Startup, setup_shapes)
+        .add_systems(Update, pulse_colors)
+        .run()
+}
+
+#[derive(Component)]
+struct Pulsing;
+
+fn setup_shapes(mut commands: Commands) {
+    commands.spawn(Camera2dBundle::default());
+
+    commands.spawn((
+        VelloShapeBundle {
+            shape: VelloShape::rounded_rect(160.0, 100.0, 16.0),
+            transform: Transform::from_xyz(-250.0, 0.0, 0.0),
+            debug_visualizations: DebugVisualizations::Visible,
+            ..default()
+        },
+        VelloFill::new(peniko::Color::rgb8(0x4a, 0x90, 0xe2)),
+        Pulsing,
+    ));
+
+    commands.spawn((
+        VelloShapeBundle {
+            shape: VelloShape::circle(60.0),
+            transform: Transform::from_xyz(-40.0, 0.0, 0.0),
+            ..default()
+        },
+        VelloStroke::new(8.0, peniko::Color::WHITE)
+            .with_caps(kurbo::Cap::Round)
+            .with_dashes(0.0, [20.0, 12.0]),
+    ));
+
+    let star = (0..10)
+        .map(|i| {
+            let angle = std::f64::consts::TAU * i as f64 / 10.0;
+            let radius = if i % 2 == 0 { 70.0 } else { 30.0 };
+            kurbo::Point::new(angle.sin() * radius, -angle.cos() * radius)
+        })
+        .collect();
+    commands.spawn((
+        VelloShapeBundle {
+            shape: VelloShape::Polygon(star),
+            transform: Transform::from_xyz(160.0, 0.0, 0.0),
+            ..default()
+        },
+        VelloFill::new(peniko::Color::rgb8(0xff, 0xd7, 0x00)),
+        VelloStroke::new(4.0, peniko::Color::rgb8(0xff, 0xa5, 0x00)).with_join(kurbo::Join::Round),
+    ));
+
+    commands.spawn((
+        VelloShapeBundle {
+            shape: VelloShape::line((0.0, 0.0), (600.0, 0.0)),
+            transform: Transform::from_xyz(0.0, -120.0, 0.0),
+            ..default()
+        },
+        VelloStroke::new(2.0, peniko::Color::rgb8(0x80, 0x80, 0x80)),
+    ));
+}
+
+/// Changing the fill re-encodes the shape, while only moving it doesn't.
+fn pulse_colors(mut query: Query<&mut VelloFill, With<Pulsing>>, time: Res<Time>) {
+    let t = time.elapsed_seconds().sin().mul_add(0.5, 0.5) as f64;
+    for mut fill in query.iter_mut() {
+        fill.brush = peniko::Color::rgb(0.3 + 0.7 * t, 0.56, 0.89 - 0.6 * t).into();
+    }
+}
@@ -0,0 +1,76 @@
+use bevy::asset::AssetMetaCheck;
+use bevy::prelude::*;
+use bevy_vello::vello::{kurbo, peniko};
+use bevy_vello::{prelude::*, VelloPlugin};
+
+fn main() {
+    App::new()
+        .insert_resource(AssetMetaCheck::Never)
+        .add_plugins(DefaultPlugins)
+        .add_plugins(VelloPlugin)
+        .add_systems(Startup, setup)
+        .add_systems(Update, (simple_animation, rotate_cube))
+        .run()
+}
+
+#[derive(Component)]
+struct Cube;
+
+fn setup(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    // The scene is rasterized into this texture every frame, instead of onto a camera.
+    let texture = VelloTexture::new(&mut images, UVec2::new(512, 512));
+    let material = materials.add(StandardMaterial {
+        base_color_texture: Some(texture.image().clone()),
+        unlit: true,
+        ..default()
+    });
+    commands.spawn((VelloSceneBundle::default(), texture));
+
+    commands.spawn((
+        PbrBundle {
+            mesh: meshes.add(Cuboid::default()),
+            material,
+            ..default()
+        },
+        Cube,
+    ));
+    commands.spawn(Camera3dBundle {
+        transform: Transform::from_xyz(0.0, 1.5, 2.5).looking_at(Vec3::ZERO, Vec3::Y),
+        ..default()
+    });
+}
+
+fn simple_animation(mut query_scene: Query<&mut VelloScene>, time: Res<Time>) {
+    let sin_time = time.elapsed_seconds().sin().mul_add(0.5, 0.5);
+    let mut scene = query_scene.single_mut();
+
+    // Reset scene every frame
+    *scene = VelloScene::default();
+
+    // The texture background
+    scene.fill(
+        peniko::Fill::NonZero,
+        kurbo::Affine::default(),
+        peniko::Color::WHITE,
+        None,
+        &kurbo::Rect::new(-256.0, -256.0, 256.0, 256.0),
+    );
+    scene.fill(
+        peniko::Fill::NonZero,
+        kurbo::Affine::rotate(std::f64::consts::TAU * sin_time as f64),
+        peniko::Color::rgb(0.2, 0.4, 1.0),
+        None,
+        &kurbo::RoundedRect::new(-150.0, -150.0, 150.0, 150.0, (sin_time as f64) * 150.0),
+    );
+}
+
+fn rotate_cube(mut query: Query<&mut Transform, With<Cube>>, time: Res<Time>) {
+    for mut transform in query.iter_mut() {
+        transform.rotate_y(time.delta_seconds() * 0.5);
+    }
+}
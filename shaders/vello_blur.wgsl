@@ -0,0 +1,81 @@
+// One separable pass of dilating or blurring shadow silhouettes or canvases.
+// A radius of 0 copies the input.
+
+struct Params {
+    // One texel along the pass axis
+    direction: vec2<f32>,
+    // In texels
+    radius: f32,
+    flags: u32,
+};
+
+// The input has straight, not premultiplied, alpha
+const STRAIGHT_INPUT: u32 = 1u;
+// Dilate rather than blur
+const DILATE: u32 = 2u;
+// Decode the sRGB encoded input into linear color, for float outputs
+const LINEAR_OUTPUT: u32 = 4u;
+
+@group(0) @binding(0)
+var input: texture_2d<f32>;
+@group(0) @binding(1)
+var<uniform> params: Params;
+
+// A triangle covering the whole target
+@vertex
+fn vertex(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32(index >> 1u), f32(index & 1u)) * 2.0;
+    return vec4<f32>(uv * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0), 0.0, 1.0);
+}
+
+fn linear_from_srgb(srgb: vec3<f32>) -> vec3<f32> {
+    let low = srgb / 12.92;
+    let high = pow((srgb + 0.055) / 1.055, vec3<f32>(2.4));
+    return select(low, high, srgb > vec3<f32>(0.04045));
+}
+
+// The premultiplied input color at `coords`, transparent outside the input
+fn load(coords: vec2<i32>) -> vec4<f32> {
+    let size = vec2<i32>(textureDimensions(input));
+    if any(coords < vec2<i32>(0)) || any(coords >= size) {
+        return vec4<f32>(0.0);
+    }
+    var color = textureLoad(input, coords, 0);
+    if (params.flags & LINEAR_OUTPUT) != 0u {
+        // Colors are decoded straight
+        if (params.flags & STRAIGHT_INPUT) == 0u {
+            color = vec4<f32>(color.rgb / max(color.a, 0.0001), color.a);
+        }
+        return vec4<f32>(linear_from_srgb(color.rgb) * color.a, color.a);
+    }
+    if (params.flags & STRAIGHT_INPUT) != 0u {
+        return vec4<f32>(color.rgb * color.a, color.a);
+    }
+    return color;
+}
+
+@fragment
+fn fragment(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let center = vec2<i32>(position.xy);
+    let direction = vec2<i32>(params.direction);
+    let taps = i32(ceil(params.radius));
+
+    if (params.flags & DILATE) != 0u {
+        var color = vec4<f32>(0.0);
+        for (var i = -taps; i <= taps; i++) {
+            color = max(color, load(center + direction * i));
+        }
+        return color;
+    }
+
+    // A gaussian that fades out at the radius, at three standard deviations
+    let sigma = max(params.radius / 3.0, 0.0001);
+    var sum = vec4<f32>(0.0);
+    var weights = 0.0;
+    for (var i = -taps; i <= taps; i++) {
+        let weight = exp(-f32(i * i) / (2.0 * sigma * sigma));
+        sum += load(center + direction * i) * weight;
+        weights += weight;
+    }
+    return sum / weights;
+}
@@ -7,6 +7,34 @@ var texture: texture_2d<f32>;
 @group(2) @binding(1)
 var texture_sampler: sampler;
 
+struct PostProcessing {
+    // Above 0 once the canvas was blurred over its shadows, leaving it
+    // premultiplied
+    blur_radius: f32,
+    bloom_intensity: f32,
+    bloom_threshold: f32,
+    exposure: f32,
+    contrast: f32,
+    saturation: f32,
+    vignette_intensity: f32,
+    vignette_radius: f32,
+    vignette_smoothness: f32,
+    tint: vec4<f32>,
+};
+
+@group(2) @binding(2)
+var<uniform> post_processing: PostProcessing;
+// Premultiplied shadows, beneath the canvas
+@group(2) @binding(3)
+var shadows: texture_2d<f32>;
+@group(2) @binding(4)
+var shadows_sampler: sampler;
+// The premultiplied canvas over its shadows, blurred by the bloom radius
+@group(2) @binding(5)
+var glow: texture_2d<f32>;
+@group(2) @binding(6)
+var glow_sampler: sampler;
+
 // returns the (0-1, 0-1) position within the given viewport for the current buffer coords .
 // buffer coords can be obtained from `@builtin(position).xy`.
 // the view uniform struct contains the current camera viewport in `view.viewport`.
@@ -49,13 +77,66 @@ fn linear_from_srgba(srgba: vec4<f32>) -> vec4<f32> {
         srgba.a);
 }
 
+// Rec. 709 luminance
+const LUMA: vec3<f32> = vec3<f32>(0.2126, 0.7152, 0.0722);
+
+// Premultiplied sRGB encoded color as premultiplied linear color
+fn linear_from_premultiplied(color: vec4<f32>) -> vec4<f32> {
+    let linear = linear_from_srgba(vec4<f32>(color.rgb / max(color.a, 0.0001), color.a));
+    return vec4<f32>(linear.rgb * color.a, color.a);
+}
+
+// Samples the canvas over its shadows as premultiplied linear color
+fn sample_canvas(uv: vec2<f32>) -> vec4<f32> {
+    let canvas = textureSampleLevel(texture, texture_sampler, uv, 0.0);
+#ifdef FLOAT_CANVAS
+    // Converted into linear color over its shadows already
+    return canvas;
+#else
+    // A blurred canvas already has its shadows drawn into it
+    if post_processing.blur_radius > 0.0 {
+        return linear_from_premultiplied(canvas);
+    }
+    let color = linear_from_srgba(canvas);
+    let shadow = linear_from_premultiplied(textureSampleLevel(shadows, shadows_sampler, uv, 0.0));
+    return vec4<f32>(color.rgb * color.a, color.a) + shadow * (1.0 - color.a);
+#endif
+}
+
 @fragment
 fn fragment(
     @builtin(position) position: vec4<f32>,
     #import bevy_sprite::mesh2d_vertex_output
 ) -> @location(0) vec4<f32> {
     let uvs = coords_to_viewport_uv(position.xy, view.viewport);
-    let color = textureSample(texture, texture_sampler, uvs);
-    let color_converted = linear_from_srgba(color);
-    return color_converted;
+
+    var color = sample_canvas(uvs);
+
+    if post_processing.bloom_intensity > 0.0 {
+        let bloom = linear_from_premultiplied(textureSampleLevel(glow, glow_sampler, uvs, 0.0));
+        let luma = dot(bloom.rgb, LUMA);
+        let bright = max(luma - post_processing.bloom_threshold, 0.0) / max(luma, 0.0001);
+        color += bloom * bright * post_processing.bloom_intensity;
+    }
+
+    // Grade and vignette straight colors
+    var rgb = color.rgb / max(color.a, 0.0001);
+    rgb *= exp2(post_processing.exposure);
+    rgb = max((rgb - 0.18) * post_processing.contrast + 0.18, vec3<f32>(0.0));
+    rgb = max(mix(vec3<f32>(dot(rgb, LUMA)), rgb, post_processing.saturation), vec3<f32>(0.0));
+    rgb *= post_processing.tint.rgb;
+
+    // 0 at the center of the viewport, 1 in its corners
+    let distance = length(uvs - 0.5) * sqrt(2.0);
+    let vignette = smoothstep(
+        post_processing.vignette_radius - post_processing.vignette_smoothness,
+        post_processing.vignette_radius,
+        distance,
+    );
+    rgb *= 1.0 - post_processing.vignette_intensity * vignette;
+
+    // Premultiplied and unclamped, so HDR targets keep values above 1.0 for
+    // bloom and tonemapping
+    let alpha = clamp(color.a, 0.0, 1.0);
+    return vec4<f32>(rgb * alpha, alpha);
 }
@@ -39,34 +39,37 @@ fn render_asset_debug(
     query_cam: Query<(&Camera, &GlobalTransform, &OrthographicProjection), With<Camera2d>>,
     mut gizmos: Gizmos,
 ) {
-    let Ok((camera, view, projection)) = query_cam.get_single() else {
-        return;
-    };
-
-    // Show vectors
+    let world_scale = world_gizmo_scale(&query_cam);
     for (vector, alignment, gtransform, space, z_fn, _) in query_vectors
         .iter()
         .filter(|(_, _, _, _, _, d)| **d == DebugVisualizations::Visible)
     {
-        if let Some(vector) = vectors.get(vector) {
-            match space {
-                CoordinateSpace::WorldSpace => {
-                    // Origin
-                    let origin = gtransform.translation().xy();
-                    draw_origin(&mut gizmos, projection, origin);
-                    // Bounding box
-                    let gtransform = &alignment.compute(vector, gtransform);
-                    let rect_center = gtransform.translation().xy();
-                    let rect = vector.bb_in_world_space(gtransform);
-                    draw_bounding_box(&mut gizmos, z_fn, rect_center, rect.size());
-                }
-                CoordinateSpace::ScreenSpace => {
+        let Some(vector) = vectors.get(vector) else {
+            continue;
+        };
+        match space {
+            // Gizmos are drawn in world space, so these are drawn once and
+            // seen by every camera
+            CoordinateSpace::WorldSpace => {
+                // Origin
+                let origin = gtransform.translation().xy();
+                draw_origin(&mut gizmos, world_scale, origin);
+                // Bounding box
+                let gtransform = &alignment.compute(vector, gtransform);
+                let rect_center = gtransform.translation().xy();
+                let rect = vector.bb_in_world_space(gtransform);
+                draw_bounding_box(&mut gizmos, z_fn, rect_center, rect.size());
+            }
+            // Screen space content is projected into the world of each 2D
+            // camera, across all windows and render targets
+            CoordinateSpace::ScreenSpace => {
+                for (camera, view, projection) in query_cam.iter() {
                     // Origin
                     let origin = gtransform.translation().xy();
                     let Some(origin) = camera.viewport_to_world_2d(view, origin) else {
                         continue;
                     };
-                    draw_origin(&mut gizmos, projection, origin);
+                    draw_origin(&mut gizmos, projection.scale, origin);
                     // Bounding box
                     let gtransform = &alignment.compute(vector, gtransform);
                     let rect_center = gtransform.translation().xy();
@@ -100,58 +103,60 @@ fn render_text_debug(
     fonts: Res<Assets<VelloFont>>,
     mut gizmos: Gizmos,
 ) {
-    let Ok((camera, view, projection)) = query_cam.get_single() else {
-        return;
-    };
-
-    // Show world-space vectors
+    let world_scale = world_gizmo_scale(&query_cam);
     for (font, text, alignment, gtransform, space, _) in query_world
         .iter()
         .filter(|(_, _, _, _, _, d)| **d == DebugVisualizations::Visible)
     {
-        if let Some(font) = fonts.get(font) {
-            let rect = text.bb_in_world_space(font, gtransform);
-            let mut origin = gtransform.translation().xy();
-            match space {
-                CoordinateSpace::WorldSpace => {
-                    draw_origin(&mut gizmos, projection, origin);
-                    let size = rect.size();
-                    let (width, height) = size.into();
-                    match alignment {
-                        VelloTextAlignment::BottomLeft => {}
-                        VelloTextAlignment::Bottom => {
-                            origin.x += -width / 2.0;
-                        }
-                        VelloTextAlignment::BottomRight => {
-                            origin.x += -width;
-                        }
-                        VelloTextAlignment::TopLeft => {
-                            origin.y += -height;
-                        }
-                        VelloTextAlignment::Left => {
-                            origin.y += -height / 2.0;
-                        }
-                        VelloTextAlignment::Top => {
-                            origin.x += -width / 2.0;
-                            origin.y += -height;
-                        }
-                        VelloTextAlignment::Center => {
-                            origin.x += -width / 2.0;
-                            origin.y += -height / 2.0;
-                        }
-                        VelloTextAlignment::TopRight => {
-                            origin.x += -width;
-                            origin.y += -height;
-                        }
-                        VelloTextAlignment::Right => {
-                            origin.x += -width;
-                            origin.y += -height / 2.0;
-                        }
-                    };
-                    let rect_center = origin + rect.size() / 2.0;
-                    gizmos.rect_2d(rect_center, 0.0, rect.size(), Color::WHITE);
-                }
-                CoordinateSpace::ScreenSpace => {
+        let Some(font) = fonts.get(font) else {
+            continue;
+        };
+        match space {
+            // Drawn once and seen by every camera, like assets
+            CoordinateSpace::WorldSpace => {
+                let rect = text.bb_in_world_space(font, gtransform);
+                let mut origin = gtransform.translation().xy();
+                draw_origin(&mut gizmos, world_scale, origin);
+                let size = rect.size();
+                let (width, height) = size.into();
+                match alignment {
+                    VelloTextAlignment::BottomLeft => {}
+                    VelloTextAlignment::Bottom => {
+                        origin.x += -width / 2.0;
+                    }
+                    VelloTextAlignment::BottomRight => {
+                        origin.x += -width;
+                    }
+                    VelloTextAlignment::TopLeft => {
+                        origin.y += -height;
+                    }
+                    VelloTextAlignment::Left => {
+                        origin.y += -height / 2.0;
+                    }
+                    VelloTextAlignment::Top => {
+                        origin.x += -width / 2.0;
+                        origin.y += -height;
+                    }
+                    VelloTextAlignment::Center => {
+                        origin.x += -width / 2.0;
+                        origin.y += -height / 2.0;
+                    }
+                    VelloTextAlignment::TopRight => {
+                        origin.x += -width;
+                        origin.y += -height;
+                    }
+                    VelloTextAlignment::Right => {
+                        origin.x += -width;
+                        origin.y += -height / 2.0;
+                    }
+                };
+                let rect_center = origin + rect.size() / 2.0;
+                gizmos.rect_2d(rect_center, 0.0, rect.size(), Color::WHITE);
+            }
+            // Projected into the world of each 2D camera, across all windows
+            // and render targets
+            CoordinateSpace::ScreenSpace => {
+                for (camera, view, projection) in query_cam.iter() {
                     let Some(rect) = text.bb_in_screen_space(font, gtransform, camera, view) else {
                         continue;
                     };
@@ -160,7 +165,7 @@ fn render_text_debug(
                     else {
                         continue;
                     };
-                    draw_origin(&mut gizmos, projection, origin);
+                    draw_origin(&mut gizmos, projection.scale, origin);
                     let size = rect.size();
                     let (width, height) = size.into();
                     match alignment {
@@ -207,15 +212,27 @@ fn render_text_debug(
     }
 }
 
+/// The scale of world space origin markers, which are drawn once for all
+/// cameras: the projection scale of the top-most active 2D camera.
+fn world_gizmo_scale(
+    query_cam: &Query<(&Camera, &GlobalTransform, &OrthographicProjection), With<Camera2d>>,
+) -> f32 {
+    query_cam
+        .iter()
+        .filter(|(camera, ..)| camera.is_active)
+        .max_by_key(|(camera, ..)| camera.order)
+        .map_or(1.0, |(_, _, projection)| projection.scale)
+}
+
 /// A helper method to draw text gizmos.
-fn draw_origin(gizmos: &mut Gizmos, projection: &OrthographicProjection, origin: Vec2) {
-    let from = origin + RED_X_SIZE * Vec2::splat(1.0) * projection.scale;
-    let to = origin + RED_X_SIZE * Vec2::splat(-1.0) * projection.scale;
+fn draw_origin(gizmos: &mut Gizmos, scale: f32, origin: Vec2) {
+    let from = origin + RED_X_SIZE * Vec2::splat(1.0) * scale;
+    let to = origin + RED_X_SIZE * Vec2::splat(-1.0) * scale;
 
     gizmos.line_2d(from, to, Color::RED);
 
-    let from = origin + RED_X_SIZE * Vec2::new(1.0, -1.0) * projection.scale;
-    let to = origin + RED_X_SIZE * Vec2::new(-1.0, 1.0) * projection.scale;
+    let from = origin + RED_X_SIZE * Vec2::new(1.0, -1.0) * scale;
+    let to = origin + RED_X_SIZE * Vec2::new(-1.0, 1.0) * scale;
 
     gizmos.line_2d(from, to, Color::RED);
 }
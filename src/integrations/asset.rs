@@ -1,6 +1,7 @@
 use crate::VectorFile;
 use bevy::prelude::*;
 use bevy::reflect::TypePath;
+use bevy::render::render_asset::RenderAsset;
 
 #[derive(Asset, TypePath, Clone)]
 pub struct VelloAsset {
@@ -11,7 +12,34 @@ pub struct VelloAsset {
     pub alpha: f32,
 }
 
+impl RenderAsset for VelloAsset {
+    type PreparedAsset = VelloAsset;
+
+    type Param = ();
+
+    fn asset_usage(&self) -> bevy::render::render_asset::RenderAssetUsages {
+        Default::default()
+    }
+
+    fn prepare_asset(
+        self,
+        _param: &mut bevy::ecs::system::SystemParamItem<Self::Param>,
+    ) -> Result<Self::PreparedAsset, bevy::render::render_asset::PrepareAssetError<Self>> {
+        Ok(self)
+    }
+}
+
 impl VelloAsset {
+    /// The bounds of the asset placed by `alignment`, in the local space of
+    /// its entity.
+    pub(crate) fn local_bounds(&self, alignment: &VelloAssetAlignment) -> Rect {
+        let center = alignment
+            .compute(self, &GlobalTransform::IDENTITY)
+            .translation()
+            .truncate();
+        Rect::from_center_size(center, Vec2::new(self.width, self.height))
+    }
+
     /// Returns the bounding box in world space
     pub fn bb_in_world_space(&self, gtransform: &GlobalTransform) -> Rect {
         // Convert local coordinates to world coordinates
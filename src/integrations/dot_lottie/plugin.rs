@@ -7,6 +7,7 @@ impl Plugin for DotLottieIntegrationPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
         // TODO: Add .lottie loader
         app.add_systems(PostUpdate, systems::advance_dot_lottie_playheads)
+            .add_systems(Update, systems::insert_player_interactions)
             .add_systems(
                 Last,
                 (systems::run_transitions, systems::transition_state)
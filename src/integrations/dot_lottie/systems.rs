@@ -1,9 +1,11 @@
 use super::DotLottiePlayer;
 use crate::integrations::lottie::PlaybackPlayMode;
+use crate::interaction::{VelloInteraction, VelloPointerEvent};
 use crate::{
     PlaybackDirection, PlaybackLoopBehavior, PlaybackOptions, PlayerTransition, Playhead,
     VectorFile, VelloAsset,
 };
+use bevy::ecs::entity::EntityHashSet;
 use bevy::prelude::*;
 use bevy::utils::Instant;
 use std::time::Duration;
@@ -135,34 +137,51 @@ pub fn advance_dot_lottie_playheads(
     }
 }
 
+/// Give every dotLottie player a [`VelloInteraction`], whose
+/// [`VelloPointerEvent`]s drive its pointer transitions.
+pub fn insert_player_interactions(
+    mut commands: Commands,
+    query_player: Query<Entity, (With<DotLottiePlayer>, Without<VelloInteraction>)>,
+) {
+    for entity in query_player.iter() {
+        commands.entity(entity).insert(VelloInteraction::default());
+    }
+}
+
 pub fn run_transitions(
     mut query_player: Query<(
+        Entity,
         &mut DotLottiePlayer,
         &Playhead,
         &PlaybackOptions,
-        &GlobalTransform,
         &mut Handle<VelloAsset>,
     )>,
     mut assets: ResMut<Assets<VelloAsset>>,
-    windows: Query<&Window>,
-    query_view: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
-    buttons: Res<ButtonInput<MouseButton>>,
-    mut hovered: Local<bool>,
+    mut pointer_events: EventReader<VelloPointerEvent>,
 ) {
-    let Ok(window) = windows.get_single() else {
-        return;
-    };
-    let Ok((camera, view)) = query_view.get_single() else {
-        return;
-    };
-
-    let pointer_pos = window
-        .cursor_position()
-        .and_then(|cursor| camera.viewport_to_world(view, cursor))
-        .map(|ray| ray.origin.truncate());
+    // The players the pointer entered, left or clicked this frame
+    let mut entered = EntityHashSet::default();
+    let mut left = EntityHashSet::default();
+    let mut clicked = EntityHashSet::default();
+    for event in pointer_events.read() {
+        match *event {
+            VelloPointerEvent::Over { entity } => {
+                entered.insert(entity);
+            }
+            VelloPointerEvent::Out { entity } => {
+                left.insert(entity);
+            }
+            VelloPointerEvent::Down {
+                entity,
+                button: MouseButton::Left,
+            } => {
+                clicked.insert(entity);
+            }
+            _ => {}
+        }
+    }
 
-    for (mut player, playhead, options, gtransform, current_asset_handle) in query_player.iter_mut()
-    {
+    for (entity, mut player, playhead, options, current_asset_handle) in query_player.iter_mut() {
         if player.stopped {
             continue;
         }
@@ -176,26 +195,6 @@ pub fn run_transitions(
                 )
             });
 
-        let is_inside = {
-            match pointer_pos {
-                Some(pointer_pos) => {
-                    let local_transform = current_asset
-                        .local_transform_center
-                        .compute_matrix()
-                        .inverse();
-                    let transform = gtransform.compute_matrix() * local_transform;
-                    let mouse_local = transform
-                        .inverse()
-                        .transform_point3(pointer_pos.extend(0.0));
-                    mouse_local.x <= current_asset.width
-                        && mouse_local.x >= 0.0
-                        && mouse_local.y >= -current_asset.height
-                        && mouse_local.y <= 0.0
-                }
-                None => false,
-            }
-        };
-
         for transition in player.state().transitions.iter() {
             match transition {
                 PlayerTransition::OnAfter { state, secs } => {
@@ -239,25 +238,21 @@ pub fn run_transitions(
                     }
                 }
                 PlayerTransition::OnMouseEnter { state } => {
-                    if is_inside {
+                    if entered.contains(&entity) {
                         player.next_state = Some(state);
-                        *hovered = true;
                         break;
                     }
                 }
                 PlayerTransition::OnMouseClick { state } => {
-                    if is_inside && buttons.just_pressed(MouseButton::Left) {
+                    if clicked.contains(&entity) {
                         player.next_state = Some(state);
                         break;
                     }
                 }
                 PlayerTransition::OnMouseLeave { state } => {
-                    if *hovered && !is_inside {
+                    if left.contains(&entity) {
                         player.next_state = Some(state);
-                        *hovered = false;
                         break;
-                    } else if is_inside {
-                        *hovered = true;
                     }
                 }
                 PlayerTransition::OnShow { state } => {
@@ -6,6 +6,10 @@ pub enum VectorLoaderError {
     Io(#[from] std::io::Error),
     #[error("Could not parse utf-8: {0}")]
     FromStrUtf8(#[from] std::str::Utf8Error),
+    #[error("Could not parse svg path data: {0}")]
+    PathData(#[from] vello::kurbo::SvgParseError),
+    #[error("Could not parse svg transform: {0}")]
+    Transform(String),
     #[cfg(feature = "svg")]
     #[error("Could not parse svg: {0}")]
     Usvg(#[from] vello_svg::usvg::Error),
@@ -24,6 +24,8 @@ pub use asset::{VelloAsset, VelloAssetAlignment};
 pub enum VectorFile {
     #[cfg(feature = "svg")]
     Svg(std::sync::Arc<vello::Scene>),
+    /// A scene encoded at runtime, like a [`VelloShape`](crate::shape::VelloShape).
+    Scene(std::sync::Arc<vello::Scene>),
     #[cfg(feature = "lottie")]
     Lottie(std::sync::Arc<velato::Composition>),
 }
@@ -0,0 +1,293 @@
+//! Pointer events for Vello content.
+
+use crate::picking::{
+    pointer_viewport_position, pointer_world_position, VelloPickable, VelloPicking,
+};
+use crate::render::{screen_space_asset_matrix, vello_affine_coeffs};
+use crate::text::VelloTextAlignment;
+use crate::{CoordinateSpace, VelloAsset, VelloAssetAlignment, VelloFont, VelloText};
+use bevy::ecs::entity::EntityHashMap;
+use bevy::math::Affine2;
+use bevy::prelude::*;
+use bevy::render::primitives::Aabb;
+use bevy::window::PrimaryWindow;
+
+/// Tracks the pointer over this entity's Vello content, sending
+/// [`VelloPointerEvent`]s.
+///
+/// Assets are hovered inside their bounds placed by their
+/// [`VelloAssetAlignment`], and text inside its measured bounds, in world or
+/// screen space. A [`VelloScene`](crate::VelloScene) is hovered inside its
+/// [`Aabb`], if it has one. Entities that are also [`VelloPickable`] are
+/// hovered by the geometry they draw instead.
+///
+/// Every entity is tracked on its own, so overlapping entities are hovered
+/// together. They are all pressed and clicked together too, unless
+/// [`VelloPicking::topmost`] is set.
+#[derive(Component, Clone, Debug, Default)]
+pub struct VelloInteraction {
+    hovered: bool,
+    /// The buttons pressed over this entity that haven't been released yet.
+    pressed: Vec<MouseButton>,
+}
+
+impl VelloInteraction {
+    /// Whether the pointer is over this entity.
+    pub fn is_hovered(&self) -> bool {
+        self.hovered
+    }
+
+    /// Whether `button` was pressed over this entity and is still held, even
+    /// if the pointer has left it since.
+    pub fn is_pressed(&self, button: MouseButton) -> bool {
+        self.pressed.contains(&button)
+    }
+}
+
+/// A pointer event on an entity with a [`VelloInteraction`].
+#[derive(Event, Clone, Copy, Debug, PartialEq)]
+pub enum VelloPointerEvent {
+    /// The pointer moved onto the entity.
+    Over { entity: Entity },
+    /// The pointer moved off the entity.
+    Out { entity: Entity },
+    /// A button was pressed over the entity.
+    Down { entity: Entity, button: MouseButton },
+    /// A button was released over the entity.
+    Up { entity: Entity, button: MouseButton },
+    /// A button was pressed and released over the entity.
+    Click { entity: Entity, button: MouseButton },
+    /// The pointer moved while a button pressed over the entity is held.
+    /// `delta` is in world units for world space entities, and in logical
+    /// pixels, y down, for screen space entities.
+    Drag {
+        entity: Entity,
+        button: MouseButton,
+        delta: Vec2,
+    },
+}
+
+impl VelloPointerEvent {
+    /// The entity this event happened on.
+    pub fn entity(&self) -> Entity {
+        match self {
+            VelloPointerEvent::Over { entity }
+            | VelloPointerEvent::Out { entity }
+            | VelloPointerEvent::Down { entity, .. }
+            | VelloPointerEvent::Up { entity, .. }
+            | VelloPointerEvent::Click { entity, .. }
+            | VelloPointerEvent::Drag { entity, .. } => *entity,
+        }
+    }
+}
+
+/// Where the pointer is, in each coordinate space.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) struct Pointer {
+    world: Vec2,
+    /// Logical pixels from the top left of the viewport.
+    screen: Vec2,
+}
+
+impl Pointer {
+    fn get(&self, space: CoordinateSpace) -> Vec2 {
+        match space {
+            CoordinateSpace::WorldSpace => self.world,
+            CoordinateSpace::ScreenSpace => self.screen,
+        }
+    }
+}
+
+/// Track the pointer over every [`VelloInteraction`] and send their
+/// [`VelloPointerEvent`]s.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn update_vello_interactions(
+    mut interactions: Query<(
+        Entity,
+        &mut VelloInteraction,
+        &GlobalTransform,
+        &CoordinateSpace,
+        &InheritedVisibility,
+        (
+            Option<(&Handle<VelloAsset>, &VelloAssetAlignment)>,
+            Option<(&Handle<VelloFont>, &VelloText, &VelloTextAlignment)>,
+            Option<&Aabb>,
+            Option<&Node>,
+        ),
+        Has<VelloPickable>,
+    )>,
+    assets: Res<Assets<VelloAsset>>,
+    fonts: Res<Assets<VelloFont>>,
+    picking: Res<VelloPicking>,
+    windows: Query<(Entity, &Window)>,
+    primary_window: Query<Entity, With<PrimaryWindow>>,
+    cameras: Query<(Entity, &Camera, &GlobalTransform), With<Camera2d>>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    mut events: EventWriter<VelloPointerEvent>,
+    mut last_pointer: Local<Option<Pointer>>,
+) {
+    let primary_window = primary_window.get_single().ok();
+    let pointer = pointer_viewport_position(&windows, primary_window, &cameras)
+        .zip(pointer_world_position(&windows, primary_window, &cameras))
+        .map(|((_, screen), world)| Pointer { world, screen });
+    let moved_from = last_pointer.filter(|last| Some(*last) != pointer);
+    *last_pointer = pointer;
+
+    let mut hovered_entities = EntityHashMap::default();
+    for (entity, _, transform, space, visibility, (asset, text, aabb, node), pickable) in
+        interactions.iter()
+    {
+        let hovered = match pointer {
+            _ if !visibility.get() => false,
+            None => false,
+            Some(_) if pickable => picking.is_hit(entity),
+            Some(pointer) => {
+                let bounds = if let Some((handle, alignment)) = asset {
+                    assets.get(handle).map(|asset| {
+                        ContentBounds::Asset(asset, asset.local_bounds(alignment), alignment, node)
+                    })
+                } else if let Some((handle, text, alignment)) = text {
+                    fonts
+                        .get(handle)
+                        .map(|font| ContentBounds::Local(font.local_bounds(text, *alignment)))
+                } else {
+                    aabb.map(|aabb| {
+                        ContentBounds::Local(Rect::from_center_half_size(
+                            aabb.center.truncate(),
+                            aabb.half_extents.truncate(),
+                        ))
+                    })
+                };
+                bounds.is_some_and(|bounds| bounds.contains(transform, *space, pointer.get(*space)))
+            }
+        };
+        if hovered {
+            hovered_entities.insert(entity, (*space, transform.translation().z));
+        }
+    }
+
+    // Buttons go to the top-most pick, or the top-most of the others by
+    // coordinate space then Z, like they're drawn
+    let topmost = picking
+        .topmost_hit()
+        .filter(|entity| hovered_entities.contains_key(entity))
+        .or_else(|| {
+            hovered_entities
+                .iter()
+                .max_by(|(_, (a_space, a_z)), (_, (b_space, b_z))| {
+                    a_space.cmp(b_space).then(a_z.total_cmp(b_z))
+                })
+                .map(|(entity, _)| *entity)
+        });
+
+    for (entity, mut interaction, _, space, ..) in interactions.iter_mut() {
+        let hovered = hovered_entities.contains_key(&entity);
+        let pressable = hovered && (!picking.topmost || topmost == Some(entity));
+
+        if hovered != interaction.hovered {
+            interaction.hovered = hovered;
+            events.send(if hovered {
+                VelloPointerEvent::Over { entity }
+            } else {
+                VelloPointerEvent::Out { entity }
+            });
+        }
+
+        if pressable {
+            for button in buttons.get_just_pressed() {
+                interaction.pressed.push(*button);
+                events.send(VelloPointerEvent::Down {
+                    entity,
+                    button: *button,
+                });
+            }
+        }
+
+        if let (Some(from), Some(to)) = (moved_from, pointer) {
+            let delta = match space {
+                CoordinateSpace::WorldSpace => to.world - from.world,
+                CoordinateSpace::ScreenSpace => to.screen - from.screen,
+            };
+            for button in interaction.pressed.iter() {
+                events.send(VelloPointerEvent::Drag {
+                    entity,
+                    button: *button,
+                    delta,
+                });
+            }
+        }
+
+        for button in buttons.get_just_released() {
+            let was_pressed = interaction.pressed.contains(button);
+            interaction.pressed.retain(|pressed| pressed != button);
+            if !pressable {
+                continue;
+            }
+            events.send(VelloPointerEvent::Up {
+                entity,
+                button: *button,
+            });
+            if was_pressed {
+                events.send(VelloPointerEvent::Click {
+                    entity,
+                    button: *button,
+                });
+            }
+        }
+    }
+}
+
+/// The bounds of an entity's content, placed like it is rendered.
+enum ContentBounds<'a> {
+    /// An asset, its bounds in local space, and what places it on screen.
+    Asset(
+        &'a VelloAsset,
+        Rect,
+        &'a VelloAssetAlignment,
+        Option<&'a Node>,
+    ),
+    /// Bounds in the local space of the entity, y up.
+    Local(Rect),
+}
+
+impl ContentBounds<'_> {
+    /// Whether the bounds contain `pointer`, in the coordinate space the
+    /// content is drawn in.
+    fn contains(&self, transform: &GlobalTransform, space: CoordinateSpace, pointer: Vec2) -> bool {
+        match (self, space) {
+            (
+                ContentBounds::Asset(_, bounds, ..) | ContentBounds::Local(bounds),
+                CoordinateSpace::WorldSpace,
+            ) => {
+                let local = transform
+                    .affine()
+                    .inverse()
+                    .transform_point3(pointer.extend(0.0));
+                bounds.contains(local.truncate())
+            }
+            // Screen space content is drawn y down, with the translation of
+            // its transform in logical pixels. See `prepare.rs`.
+            (ContentBounds::Asset(asset, _, alignment, node), CoordinateSpace::ScreenSpace) => {
+                let model_matrix =
+                    screen_space_asset_matrix(asset, alignment.compute(asset, transform), *node);
+                let encoding = screen_affine(model_matrix)
+                    .inverse()
+                    .transform_point2(pointer);
+                Rect::from_corners(Vec2::ZERO, Vec2::new(asset.width, asset.height))
+                    .contains(encoding)
+            }
+            (ContentBounds::Local(bounds), CoordinateSpace::ScreenSpace) => {
+                let encoding = screen_affine(transform.compute_matrix())
+                    .inverse()
+                    .transform_point2(pointer);
+                // The encoding is y down
+                bounds.contains(Vec2::new(encoding.x, -encoding.y))
+            }
+        }
+    }
+}
+
+/// The affine screen space content is drawn with, in logical pixels.
+fn screen_affine(raw_transform: Mat4) -> Affine2 {
+    Affine2::from_cols_array(&vello_affine_coeffs(raw_transform))
+}
@@ -10,7 +10,12 @@ pub use plugin::VelloPlugin;
 
 pub mod debug;
 pub mod integrations;
+pub mod interaction;
+pub mod picking;
 pub mod render;
+pub mod shape;
+#[cfg(all(feature = "testing", not(target_arch = "wasm32")))]
+pub mod testing;
 pub mod text;
 
 // Re-exports
@@ -21,10 +26,18 @@ pub mod prelude {
 
     pub use crate::debug::DebugVisualizations;
     pub use crate::integrations::{VectorFile, VelloAsset, VelloAssetAlignment};
-    pub use crate::render::{VelloCanvasMaterial, ZFunction};
+    pub use crate::interaction::{VelloInteraction, VelloPointerEvent};
+    pub use crate::picking::{VelloPickable, VelloPicking};
+    pub use crate::render::{
+        ImageReadback, VelloBlend, VelloBloom, VelloCanvasMaterial, VelloClip, VelloColorGrading,
+        VelloLayer, VelloOpacity, VelloPainter, VelloPostProcessing, VelloRedraw,
+        VelloRenderSettings, VelloShadow, VelloTexture, VelloVignette, ZFunction,
+    };
+    pub use crate::shape::{VelloFill, VelloShape, VelloStroke};
     pub use crate::text::{VelloFont, VelloText, VelloTextAlignment};
     pub use crate::{
-        CoordinateSpace, VelloAssetBundle, VelloScene, VelloSceneBundle, VelloTextBundle,
+        CoordinateSpace, VelloAssetBundle, VelloScene, VelloSceneBundle, VelloShapeBundle,
+        VelloTextBundle,
     };
 
     #[cfg(feature = "experimental-dotLottie")]
@@ -69,6 +82,32 @@ pub struct VelloAssetBundle {
     pub view_visibility: ViewVisibility,
 }
 
+/// A [`VelloShape`], drawn with the [`VelloFill`] and [`VelloStroke`] inserted
+/// alongside this bundle.
+#[derive(Bundle, Default)]
+pub struct VelloShapeBundle {
+    /// Shape to render
+    pub shape: VelloShape,
+    /// How the bounding shape is aligned, respective to the transform.
+    pub alignment: VelloAssetAlignment,
+    /// The coordinate space in which this shape should be rendered.
+    pub coordinate_space: CoordinateSpace,
+    /// A transform to apply to this shape
+    pub transform: Transform,
+    /// The global transform managed by Bevy
+    pub global_transform: GlobalTransform,
+    /// Use a depth-sorting function for this shape, used when rendering. By default, all shapes use the transform's Z-coordinate for depth sorting in the renderer's painter's algorithm (see [`ZFunction::TransformZ`]).
+    pub z_function: ZFunction,
+    /// Whether to render debug visualizations
+    pub debug_visualizations: DebugVisualizations,
+    /// User indication of whether an entity is visible. Propagates down the entity hierarchy.
+    pub visibility: Visibility,
+    /// Whether or not an entity is visible in the hierarchy.
+    pub inherited_visibility: InheritedVisibility,
+    /// Algorithmically-computed indication of whether an entity is visible. Should be extracted for rendering.
+    pub view_visibility: ViewVisibility,
+}
+
 #[derive(Bundle, Default)]
 pub struct VelloSceneBundle {
     /// Scene to render
@@ -112,8 +151,15 @@ pub struct VelloTextBundle {
 }
 
 /// A simple newtype component wrapper for [`vello::Scene`] for rendering.
+///
+/// The scene is shared with the render world rather than copied into it each
+/// frame. Mutating it through [`DerefMut`](std::ops::DerefMut) copies it first
+/// if a frame still holds it.
+///
+/// The bounds of a scene can't be measured, so scenes are never frustum culled
+/// unless an [`Aabb`](bevy::render::primitives::Aabb) is inserted alongside them.
 #[derive(Component, Default, Clone)]
-pub struct VelloScene(vello::Scene);
+pub struct VelloScene(std::sync::Arc<vello::Scene>);
 
 impl std::ops::Deref for VelloScene {
     type Target = vello::Scene;
@@ -125,7 +171,7 @@ impl std::ops::Deref for VelloScene {
 
 impl std::ops::DerefMut for VelloScene {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+        std::sync::Arc::make_mut(&mut self.0)
     }
 }
 
@@ -137,6 +183,6 @@ impl VelloScene {
 
 impl From<vello::Scene> for VelloScene {
     fn from(scene: vello::Scene) -> Self {
-        Self(scene)
+        Self(std::sync::Arc::new(scene))
     }
 }
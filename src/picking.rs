@@ -0,0 +1,156 @@
+//! Hit testing Vello content under the cursor, against the geometry it draws.
+
+use bevy::prelude::*;
+use bevy::render::camera::NormalizedRenderTarget;
+use bevy::render::extract_resource::ExtractResource;
+use bevy::window::PrimaryWindow;
+use std::sync::{Arc, Mutex};
+
+/// Hit tests this entity's Vello content for [`VelloPicking`].
+///
+/// Assets, shapes and [`VelloScene`](crate::VelloScene)s are hit wherever
+/// they draw anything under the cursor, inside their clips, so transparent
+/// holes like the middle of a donut are not. Lottie assets are tested at their
+/// current frame, and text by the box of each glyph.
+#[derive(Component, Default, Clone, Copy, Debug, Reflect)]
+#[reflect(Component)]
+pub struct VelloPickable;
+
+/// Where the cursor picks content.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PickPointer {
+    /// The top-most 2D camera under the cursor.
+    pub camera: Entity,
+    /// The cursor in the camera's viewport, in physical pixels.
+    pub position: Vec2,
+}
+
+/// The [`VelloPickable`] entities under the cursor.
+///
+/// Picks are rendered on the GPU with the same affines, clips and ordering as
+/// the content itself, and read back asynchronously, so they trail the cursor
+/// by a couple of frames.
+#[derive(Resource, ExtractResource, Clone, Default)]
+pub struct VelloPicking {
+    /// Only send [`VelloPointerEvent`](crate::interaction::VelloPointerEvent)
+    /// `Down`, `Up` and `Click` to the top-most hovered
+    /// [`VelloInteraction`](crate::interaction::VelloInteraction), instead of
+    /// every entity under the pointer. Off by default.
+    pub topmost: bool,
+    pointer: Option<PickPointer>,
+    hits: Vec<Entity>,
+    /// The latest hits read back by the render world, until they're taken.
+    read_back: Arc<Mutex<Option<Vec<Entity>>>>,
+}
+
+impl VelloPicking {
+    /// The pickable entities under the cursor, top-most first.
+    pub fn hits(&self) -> &[Entity] {
+        &self.hits
+    }
+
+    /// The top-most pickable entity under the cursor.
+    pub fn topmost_hit(&self) -> Option<Entity> {
+        self.hits.first().copied()
+    }
+
+    /// Whether the entity is under the cursor, even when covered by another.
+    pub fn is_hit(&self, entity: Entity) -> bool {
+        self.hits.contains(&entity)
+    }
+
+    /// Where the cursor picks, if it's over a 2D camera.
+    pub fn pointer(&self) -> Option<PickPointer> {
+        self.pointer
+    }
+
+    /// Hand the hits of a pick over to the main world.
+    pub(crate) fn publish(&self, hits: Vec<Entity>) {
+        if let Ok(mut read_back) = self.read_back.lock() {
+            read_back.replace(hits);
+        }
+    }
+}
+
+/// Track the cursor and take the hits read back by the render world.
+pub(crate) fn update_vello_picking(
+    mut picking: ResMut<VelloPicking>,
+    windows: Query<(Entity, &Window)>,
+    primary_window: Query<Entity, With<PrimaryWindow>>,
+    cameras: Query<(Entity, &Camera, &GlobalTransform), With<Camera2d>>,
+) {
+    let pointer = pointer_viewport_position(&windows, primary_window.get_single().ok(), &cameras)
+        .and_then(|(camera, cursor)| {
+            let (_, camera_component, _) = cameras.get(camera).ok()?;
+            let scale_factor = camera_component.target_scaling_factor()?;
+            Some(PickPointer {
+                camera,
+                position: cursor * scale_factor,
+            })
+        });
+    if picking.pointer != pointer {
+        picking.pointer = pointer;
+    }
+
+    let read_back = picking
+        .read_back
+        .lock()
+        .ok()
+        .and_then(|mut read_back| read_back.take());
+    // Picks still in flight when the cursor left are stale
+    let hits = if pointer.is_some() {
+        read_back
+    } else {
+        Some(Vec::new())
+    };
+    if let Some(hits) = hits {
+        if picking.hits != hits {
+            picking.hits = hits;
+        }
+    }
+}
+
+/// The top-most active 2D camera under the cursor, in whichever window the
+/// cursor is currently in, and the cursor's position in its viewport, in
+/// logical pixels.
+pub(crate) fn pointer_viewport_position(
+    windows: &Query<(Entity, &Window)>,
+    primary_window: Option<Entity>,
+    cameras: &Query<(Entity, &Camera, &GlobalTransform), With<Camera2d>>,
+) -> Option<(Entity, Vec2)> {
+    windows.iter().find_map(|(window_entity, window)| {
+        let cursor = window.cursor_position()?;
+        cameras
+            .iter()
+            .filter(|(_, camera, _)| camera.is_active)
+            .filter(|(_, camera, _)| {
+                matches!(
+                    camera.target.normalize(primary_window),
+                    Some(NormalizedRenderTarget::Window(window_ref))
+                        if window_ref.entity() == window_entity
+                )
+            })
+            .filter_map(|(entity, camera, _)| {
+                let viewport = camera.logical_viewport_rect()?;
+                viewport
+                    .contains(cursor)
+                    .then_some((entity, camera.order, cursor - viewport.min))
+            })
+            .max_by_key(|(_, order, _)| *order)
+            .map(|(entity, _, cursor)| (entity, cursor))
+    })
+}
+
+/// Returns the world position of the cursor, as seen by the top-most 2D camera
+/// underneath it, in whichever window the cursor is currently in.
+pub(crate) fn pointer_world_position(
+    windows: &Query<(Entity, &Window)>,
+    primary_window: Option<Entity>,
+    cameras: &Query<(Entity, &Camera, &GlobalTransform), With<Camera2d>>,
+) -> Option<Vec2> {
+    let (camera, cursor) = pointer_viewport_position(windows, primary_window, cameras)?;
+    let (_, camera, view) = cameras.get(camera).ok()?;
+    camera
+        .viewport_to_world(view, cursor)
+        .map(|ray| ray.origin.truncate())
+}
@@ -1,8 +1,11 @@
 use crate::debug::DebugVisualizationsPlugin;
 use crate::render::VelloRenderPlugin;
 use crate::text::VelloFontLoader;
+use crate::{interaction, picking, shape};
 use crate::{VelloAsset, VelloFont};
+use bevy::input::InputSystem;
 use bevy::prelude::*;
+use bevy::render::view::VisibilitySystems;
 
 pub struct VelloPlugin;
 
@@ -12,7 +15,22 @@ impl Plugin for VelloPlugin {
             .add_plugins(DebugVisualizationsPlugin)
             .init_asset::<VelloAsset>()
             .init_asset::<VelloFont>()
-            .init_asset_loader::<VelloFontLoader>();
+            .init_asset_loader::<VelloFontLoader>()
+            .init_resource::<picking::VelloPicking>()
+            .add_event::<interaction::VelloPointerEvent>()
+            .add_systems(
+                PreUpdate,
+                (
+                    picking::update_vello_picking,
+                    interaction::update_vello_interactions,
+                )
+                    .chain()
+                    .after(InputSystem),
+            )
+            .add_systems(
+                PostUpdate,
+                shape::encode_shapes.before(VisibilitySystems::CalculateBounds),
+            );
         #[cfg(feature = "svg")]
         app.add_plugins(crate::integrations::svg::SvgIntegrationPlugin);
         #[cfg(feature = "lottie")]
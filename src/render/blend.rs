@@ -0,0 +1,36 @@
+use bevy::prelude::*;
+use vello::peniko::{BlendMode, Compose, Mix};
+
+/// Blends the Vello content of this entity onto what is drawn beneath it as
+/// one group, with a [`BlendMode`] and a group opacity.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct VelloBlend {
+    /// How the group is mixed and composited with the content beneath it.
+    pub mode: BlendMode,
+    /// The opacity of the whole group, from 0 to 1.
+    pub opacity: f32,
+}
+
+impl Default for VelloBlend {
+    fn default() -> Self {
+        Self {
+            mode: BlendMode::new(Mix::Normal, Compose::SrcOver),
+            opacity: 1.0,
+        }
+    }
+}
+
+impl VelloBlend {
+    /// Blend with a peniko `Mix` or `Compose` mode, or both as a [`BlendMode`].
+    pub fn new(mode: impl Into<BlendMode>) -> Self {
+        Self {
+            mode: mode.into(),
+            ..default()
+        }
+    }
+
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        self.opacity = opacity;
+        self
+    }
+}
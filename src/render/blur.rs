@@ -0,0 +1,544 @@
+//! Separable blurs on the GPU, for shadows and canvas post-processing, and
+//! the conversion of canvases into float textures.
+
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{
+    BindGroup, BindGroupEntry, BindGroupLayout, BindGroupLayoutEntry, BindingResource, BindingType,
+    BlendState, Buffer, BufferBinding, BufferBindingType, BufferDescriptor, BufferUsages,
+    ColorTargetState, ColorWrites, CommandEncoderDescriptor, Extent3d, LoadOp, Operations,
+    PipelineLayoutDescriptor, RawFragmentState, RawRenderPipelineDescriptor, RawVertexState,
+    RenderPassColorAttachment, RenderPassDescriptor, RenderPipeline, ShaderModuleDescriptor,
+    ShaderSource, ShaderStages, StoreOp, Texture, TextureDescriptor, TextureDimension,
+    TextureFormat, TextureSampleType, TextureUsages, TextureView, TextureViewDescriptor,
+    TextureViewDimension, TextureViewId,
+};
+use bevy::render::renderer::{RenderDevice, RenderQueue};
+use bevy::utils::{HashMap, HashSet};
+use std::num::NonZeroU64;
+
+/// The largest blur or spread radius, in physical pixels. Larger radii are
+/// clamped to it, which bounds the taps of each blur pass.
+pub const MAX_BLUR_RADIUS: u32 = 64;
+
+/// An image blurred content is drawn into, to be sampled by a canvas.
+pub(crate) fn blur_target_image(label: &'static str, size: UVec2) -> Image {
+    let size = Extent3d {
+        width: size.x,
+        height: size.y,
+        ..default()
+    };
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: Some(label),
+            size,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Rgba8Unorm,
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..default()
+    };
+    image.resize(size);
+    image
+}
+
+/// A transparent 1x1 image, sampled by canvases in place of blurred images
+/// they don't need.
+pub(crate) fn placeholder_image() -> Image {
+    Image::new_fill(
+        Extent3d::default(),
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8Unorm,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+}
+
+/// The intermediate textures content is drawn and blurred in.
+#[derive(Clone)]
+pub struct BlurTextures {
+    size: UVec2,
+    /// The content to blur. Vello draws shadow silhouettes in here.
+    pub input: TextureView,
+    scratch: TextureView,
+    // Kept alive for their views
+    _textures: [Texture; 2],
+}
+
+impl BlurTextures {
+    fn new(device: &RenderDevice, size: UVec2) -> Self {
+        let texture = |label| {
+            device.create_texture(&TextureDescriptor {
+                label: Some(label),
+                size: Extent3d {
+                    width: size.x,
+                    height: size.y,
+                    ..default()
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: TextureFormat::Rgba8Unorm,
+                usage: TextureUsages::TEXTURE_BINDING
+                    | TextureUsages::STORAGE_BINDING
+                    | TextureUsages::RENDER_ATTACHMENT,
+                view_formats: &[],
+            })
+        };
+        let input = texture("vello_blur_input");
+        let scratch = texture("vello_blur_scratch");
+        Self {
+            size,
+            input: input.create_view(&TextureViewDescriptor::default()),
+            scratch: scratch.create_view(&TextureViewDescriptor::default()),
+            _textures: [input, scratch],
+        }
+    }
+}
+
+/// The input of a pass has straight, not premultiplied, alpha.
+const PASS_STRAIGHT_INPUT: u32 = 1;
+/// The pass dilates rather than blurs.
+const PASS_DILATE: u32 = 2;
+/// The pass decodes its sRGB encoded input into linear color.
+const PASS_LINEAR_OUTPUT: u32 = 4;
+
+/// The size of the parameters of one pass, as laid out in the shader.
+const PARAMS_SIZE: u64 = 16;
+
+/// One fullscreen pass of dilating or blurring along one axis. A radius of
+/// `0` copies the input.
+struct BlurPass<'a> {
+    input: &'a TextureView,
+    output: &'a TextureView,
+    /// One texel along the axis of the pass.
+    direction: Vec2,
+    /// In texels.
+    radius: u32,
+    flags: u32,
+    /// Draw over the output, rather than replacing it.
+    over: bool,
+    /// Clear the output before drawing.
+    clear: bool,
+    /// The output is an `Rgba16Float` texture, rather than `Rgba8Unorm`.
+    float: bool,
+}
+
+impl<'a> BlurPass<'a> {
+    fn new(input: &'a TextureView, output: &'a TextureView, direction: Vec2, radius: u32) -> Self {
+        Self {
+            input,
+            output,
+            direction,
+            radius,
+            flags: 0,
+            over: false,
+            clear: true,
+            float: false,
+        }
+    }
+}
+
+/// Dilates and blurs shadow silhouettes and canvases on the GPU, and converts
+/// canvases into float textures.
+#[derive(Resource)]
+pub struct BlurRenderer {
+    layout: BindGroupLayout,
+    /// Writes the pass output as is.
+    replace: RenderPipeline,
+    /// Draws the pass output over the target, with premultiplied alpha.
+    over: RenderPipeline,
+    /// [`Self::replace`] and [`Self::over`], for float outputs.
+    float_replace: RenderPipeline,
+    float_over: RenderPipeline,
+    /// The parameters of every pass of a submission, each at its own dynamic
+    /// offset. Rewritten before each submission.
+    params: Buffer,
+    /// How many passes `params` has room for.
+    params_capacity: usize,
+    /// The distance between the parameters of two passes.
+    params_stride: u64,
+    /// A bind group for each texture view passes read from.
+    bind_groups: HashMap<TextureViewId, BindGroup>,
+    /// The texture views read from since [`Self::retain_textures`].
+    used_inputs: HashSet<TextureViewId>,
+    textures: HashMap<AssetId<Image>, BlurTextures>,
+}
+
+impl FromWorld for BlurRenderer {
+    fn from_world(world: &mut World) -> Self {
+        let device = world.resource::<RenderDevice>();
+        let layout = device.create_bind_group_layout(
+            "vello_blur_layout",
+            &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Texture {
+                        sample_type: TextureSampleType::Float { filterable: false },
+                        view_dimension: TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        has_dynamic_offset: true,
+                        min_binding_size: NonZeroU64::new(PARAMS_SIZE),
+                    },
+                    count: None,
+                },
+            ],
+        );
+        let shader = device.create_shader_module(ShaderModuleDescriptor {
+            label: Some("vello_blur_shader"),
+            source: ShaderSource::Wgsl(include_str!("../../shaders/vello_blur.wgsl").into()),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("vello_blur_pipeline_layout"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = |format, blend| {
+            device.create_render_pipeline(&RawRenderPipelineDescriptor {
+                label: Some("vello_blur_pipeline"),
+                layout: Some(&pipeline_layout),
+                vertex: RawVertexState {
+                    module: &shader,
+                    entry_point: "vertex",
+                    buffers: &[],
+                },
+                fragment: Some(RawFragmentState {
+                    module: &shader,
+                    entry_point: "fragment",
+                    targets: &[Some(ColorTargetState {
+                        format,
+                        blend,
+                        write_mask: ColorWrites::ALL,
+                    })],
+                }),
+                primitive: default(),
+                depth_stencil: None,
+                multisample: default(),
+                multiview: None,
+            })
+        };
+        let params_stride =
+            PARAMS_SIZE.max(device.limits().min_uniform_buffer_offset_alignment as u64);
+        // A canvas takes up to six passes
+        let params_capacity = 6;
+        Self {
+            replace: pipeline(TextureFormat::Rgba8Unorm, None),
+            over: pipeline(
+                TextureFormat::Rgba8Unorm,
+                Some(BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+            ),
+            float_replace: pipeline(TextureFormat::Rgba16Float, None),
+            float_over: pipeline(
+                TextureFormat::Rgba16Float,
+                Some(BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+            ),
+            params: params_buffer(device, params_stride * params_capacity as u64),
+            params_capacity,
+            params_stride,
+            layout,
+            bind_groups: default(),
+            used_inputs: default(),
+            textures: default(),
+        }
+    }
+}
+
+fn params_buffer(device: &RenderDevice, size: u64) -> Buffer {
+    device.create_buffer(&BufferDescriptor {
+        label: Some("vello_blur_params"),
+        size,
+        usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+impl BlurRenderer {
+    /// The intermediate textures for blurring into `target`, reused across
+    /// frames while its size stays the same.
+    pub fn textures(
+        &mut self,
+        device: &RenderDevice,
+        target: AssetId<Image>,
+        size: UVec2,
+    ) -> BlurTextures {
+        self.textures
+            .entry(target)
+            .and_modify(|textures| {
+                if textures.size != size {
+                    *textures = BlurTextures::new(device, size);
+                }
+            })
+            .or_insert_with(|| BlurTextures::new(device, size))
+            .clone()
+    }
+
+    /// Drop the intermediate textures of every target not in `targets`, and
+    /// the bind groups of every texture view not read from since the last call.
+    pub fn retain_textures(&mut self, targets: &[AssetId<Image>]) {
+        self.textures.retain(|target, _| targets.contains(target));
+        let used_inputs = std::mem::take(&mut self.used_inputs);
+        self.bind_groups
+            .retain(|input, _| used_inputs.contains(input));
+    }
+
+    /// Dilate the silhouettes in `textures` by `spread` and blur them by `blur`
+    /// pixels, then draw them over `target`, or replace its contents when
+    /// `clear` is set. Both radii are clamped to [`MAX_BLUR_RADIUS`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn blur_shadows(
+        &mut self,
+        device: &RenderDevice,
+        queue: &RenderQueue,
+        textures: &BlurTextures,
+        target: &TextureView,
+        blur: u32,
+        spread: u32,
+        clear: bool,
+    ) {
+        let (blur, spread) = (blur.min(MAX_BLUR_RADIUS), spread.min(MAX_BLUR_RADIUS));
+        let mut passes = Vec::with_capacity(4);
+        let mut straight = PASS_STRAIGHT_INPUT;
+        if spread > 0 {
+            passes.push(BlurPass {
+                flags: straight | PASS_DILATE,
+                ..BlurPass::new(&textures.input, &textures.scratch, Vec2::X, spread)
+            });
+            passes.push(BlurPass {
+                flags: PASS_DILATE,
+                ..BlurPass::new(&textures.scratch, &textures.input, Vec2::Y, spread)
+            });
+            straight = 0;
+        }
+        passes.push(BlurPass {
+            flags: straight,
+            ..BlurPass::new(&textures.input, &textures.scratch, Vec2::X, blur)
+        });
+        passes.push(BlurPass {
+            over: true,
+            clear,
+            ..BlurPass::new(&textures.scratch, target, Vec2::Y, blur)
+        });
+        self.submit(device, queue, "vello_shadows", &passes);
+    }
+
+    /// Draw `canvas` over its `shadows`, then blur that by `glow_radius` into
+    /// `glow`, and by `blur` back into the canvas. A blurred canvas is left
+    /// with premultiplied alpha, and its shadows drawn into it. Both radii are
+    /// clamped to [`MAX_BLUR_RADIUS`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn blur_canvas(
+        &mut self,
+        device: &RenderDevice,
+        queue: &RenderQueue,
+        textures: &BlurTextures,
+        canvas: &TextureView,
+        shadows: Option<&TextureView>,
+        blur: u32,
+        glow: Option<(&TextureView, u32)>,
+    ) {
+        let blur = blur.min(MAX_BLUR_RADIUS);
+        let mut passes = Vec::with_capacity(6);
+        if let Some(shadows) = shadows {
+            passes.push(BlurPass::new(shadows, &textures.input, Vec2::X, 0));
+        }
+        passes.push(BlurPass {
+            flags: PASS_STRAIGHT_INPUT,
+            over: shadows.is_some(),
+            clear: shadows.is_none(),
+            ..BlurPass::new(canvas, &textures.input, Vec2::X, 0)
+        });
+        if let Some((glow, radius)) = glow {
+            let radius = radius.min(MAX_BLUR_RADIUS);
+            passes.push(BlurPass::new(
+                &textures.input,
+                &textures.scratch,
+                Vec2::X,
+                radius,
+            ));
+            passes.push(BlurPass::new(&textures.scratch, glow, Vec2::Y, radius));
+        }
+        if blur > 0 {
+            passes.push(BlurPass::new(
+                &textures.input,
+                &textures.scratch,
+                Vec2::X,
+                blur,
+            ));
+            passes.push(BlurPass::new(&textures.scratch, canvas, Vec2::Y, blur));
+        }
+        self.submit(device, queue, "vello_canvas_blur", &passes);
+    }
+
+    /// Draw `canvas` over its `shadows` into the float texture `target`, as
+    /// linear premultiplied color. A `blurred` canvas is premultiplied and
+    /// has its shadows drawn in already.
+    pub fn convert_canvas(
+        &mut self,
+        device: &RenderDevice,
+        queue: &RenderQueue,
+        canvas: &TextureView,
+        shadows: Option<&TextureView>,
+        blurred: bool,
+        target: &TextureView,
+    ) {
+        let mut passes = Vec::with_capacity(2);
+        if blurred {
+            passes.push(BlurPass {
+                flags: PASS_LINEAR_OUTPUT,
+                float: true,
+                ..BlurPass::new(canvas, target, Vec2::X, 0)
+            });
+        } else {
+            if let Some(shadows) = shadows {
+                passes.push(BlurPass {
+                    flags: PASS_LINEAR_OUTPUT,
+                    float: true,
+                    ..BlurPass::new(shadows, target, Vec2::X, 0)
+                });
+            }
+            passes.push(BlurPass {
+                flags: PASS_STRAIGHT_INPUT | PASS_LINEAR_OUTPUT,
+                over: shadows.is_some(),
+                clear: shadows.is_none(),
+                float: true,
+                ..BlurPass::new(canvas, target, Vec2::X, 0)
+            });
+        }
+        self.submit(device, queue, "vello_canvas_conversion", &passes);
+    }
+
+    /// Clear `target`, for canvases whose shadowed content went out of view.
+    pub fn clear(&self, device: &RenderDevice, queue: &RenderQueue, target: &TextureView) {
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("vello_blur_clear"),
+        });
+        encoder.begin_render_pass(&RenderPassDescriptor {
+            label: Some("vello_blur_clear_pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Clear(default()),
+                    store: StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        queue.submit([encoder.finish()]);
+    }
+
+    /// Encode `passes` in order and submit them.
+    fn submit(
+        &mut self,
+        device: &RenderDevice,
+        queue: &RenderQueue,
+        label: &'static str,
+        passes: &[BlurPass],
+    ) {
+        self.write_params(device, queue, passes);
+        for pass in passes.iter() {
+            self.prepare_bind_group(device, pass.input);
+        }
+
+        let mut encoder =
+            device.create_command_encoder(&CommandEncoderDescriptor { label: Some(label) });
+        for (i, pass) in passes.iter().enumerate() {
+            let mut render_pass = encoder.begin_render_pass(&RenderPassDescriptor {
+                label: Some("vello_blur_pass"),
+                color_attachments: &[Some(RenderPassColorAttachment {
+                    view: pass.output,
+                    resolve_target: None,
+                    ops: Operations {
+                        load: if pass.clear {
+                            LoadOp::Clear(default())
+                        } else {
+                            LoadOp::Load
+                        },
+                        store: StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            render_pass.set_pipeline(match (pass.float, pass.over) {
+                (false, false) => &self.replace,
+                (false, true) => &self.over,
+                (true, false) => &self.float_replace,
+                (true, true) => &self.float_over,
+            });
+            render_pass.set_bind_group(
+                0,
+                &self.bind_groups[&pass.input.id()],
+                &[(i as u64 * self.params_stride) as u32],
+            );
+            render_pass.draw(0..3, 0..1);
+        }
+        queue.submit([encoder.finish()]);
+    }
+
+    /// Write the parameters of `passes`, growing the buffer when they don't
+    /// fit. Queued writes land before the next submission, so every
+    /// submission can reuse the buffer from the start.
+    fn write_params(&mut self, device: &RenderDevice, queue: &RenderQueue, passes: &[BlurPass]) {
+        if passes.len() > self.params_capacity {
+            self.params_capacity = passes.len();
+            self.params = params_buffer(device, self.params_stride * passes.len() as u64);
+            // Bind groups hold on to the old buffer
+            self.bind_groups.clear();
+        }
+        let mut params = vec![0; self.params_stride as usize * passes.len()];
+        for (i, pass) in passes.iter().enumerate() {
+            let offset = i * self.params_stride as usize;
+            let bytes = &mut params[offset..offset + PARAMS_SIZE as usize];
+            bytes[0..4].copy_from_slice(&pass.direction.x.to_le_bytes());
+            bytes[4..8].copy_from_slice(&pass.direction.y.to_le_bytes());
+            bytes[8..12].copy_from_slice(&(pass.radius as f32).to_le_bytes());
+            bytes[12..16].copy_from_slice(&pass.flags.to_le_bytes());
+        }
+        queue.write_buffer(&self.params, 0, &params);
+    }
+
+    /// Make sure there's a bind group reading from `input`.
+    fn prepare_bind_group(&mut self, device: &RenderDevice, input: &TextureView) {
+        self.used_inputs.insert(input.id());
+        if self.bind_groups.contains_key(&input.id()) {
+            return;
+        }
+        let bind_group = device.create_bind_group(
+            "vello_blur_bind_group",
+            &self.layout,
+            &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::TextureView(input),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::Buffer(BufferBinding {
+                        buffer: &self.params,
+                        offset: 0,
+                        size: NonZeroU64::new(PARAMS_SIZE),
+                    }),
+                },
+            ],
+        );
+        self.bind_groups.insert(input.id(), bind_group);
+    }
+}
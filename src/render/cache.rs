@@ -0,0 +1,254 @@
+//! Encodings retained in the render world across frames.
+
+#[cfg(feature = "lottie")]
+use super::extract::ExtractedRenderAsset;
+use super::extract::ExtractedRenderText;
+use crate::text::VelloTextAlignment;
+#[cfg(feature = "lottie")]
+use crate::VelloAsset;
+use crate::{VelloFont, VelloText};
+use bevy::ecs::entity::{EntityHashMap, EntityHashSet};
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssets;
+use bevy::tasks::ComputeTaskPool;
+use vello::kurbo::Affine;
+use vello::Scene;
+
+/// Encodings of text and Lottie assets, keyed by their main world entity.
+///
+/// They are encoded without their affine, rebuilt only when what they are
+/// encoded from changes, and appended with the latest affine every frame.
+/// SVG assets and [`VelloScene`](crate::VelloScene)s share their encodings
+/// already, so they need no cache.
+#[derive(Resource, Default)]
+pub struct EncodingCache {
+    texts: EntityHashMap<Fragment>,
+    #[cfg(feature = "lottie")]
+    lotties: EntityHashMap<Fragment>,
+    /// One encoder for each parallel task, kept across frames.
+    encoders: Vec<Encoder>,
+}
+
+struct Fragment {
+    source: Source,
+    scene: Scene,
+}
+
+/// What a fragment is encoded from.
+enum Source {
+    Text {
+        font: AssetId<VelloFont>,
+        text: VelloText,
+        alignment: VelloTextAlignment,
+    },
+    #[cfg(feature = "lottie")]
+    Lottie {
+        composition: std::sync::Arc<velato::Composition>,
+        frame: f64,
+        theme: Option<crate::Theme>,
+        alpha: f32,
+    },
+}
+
+/// A fragment to encode this frame.
+enum Stale<'a> {
+    Text(&'a ExtractedRenderText, &'a VelloFont),
+    #[cfg(feature = "lottie")]
+    Lottie(
+        &'a ExtractedRenderAsset,
+        &'a std::sync::Arc<velato::Composition>,
+        f32,
+    ),
+}
+
+/// The state a parallel task encodes with.
+#[derive(Default)]
+struct Encoder {
+    #[cfg(feature = "lottie")]
+    velato_renderer: super::VelatoRenderer,
+}
+
+impl Source {
+    fn is_text(&self, text: &ExtractedRenderText) -> bool {
+        match self {
+            Source::Text {
+                font,
+                text: source,
+                alignment,
+            } => *font == text.font.id() && *source == text.text && *alignment == text.alignment,
+            #[cfg(feature = "lottie")]
+            _ => false,
+        }
+    }
+
+    #[cfg(feature = "lottie")]
+    fn is_lottie(
+        &self,
+        instance: &ExtractedRenderAsset,
+        composition: &std::sync::Arc<velato::Composition>,
+        alpha: f32,
+    ) -> bool {
+        match self {
+            // Compositions are immutable once loaded, so the same allocation
+            // is the same animation
+            Source::Lottie {
+                composition: source,
+                frame,
+                theme,
+                alpha: source_alpha,
+            } => {
+                std::sync::Arc::ptr_eq(source, composition)
+                    && *frame == instance.playhead
+                    && *theme == instance.theme
+                    && *source_alpha == alpha
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Stale<'_> {
+    fn source(&self) -> Source {
+        match self {
+            Stale::Text(text, _) => Source::Text {
+                font: text.font.id(),
+                text: text.text.clone(),
+                alignment: text.alignment,
+            },
+            #[cfg(feature = "lottie")]
+            Stale::Lottie(instance, composition, alpha) => Source::Lottie {
+                composition: (*composition).clone(),
+                frame: instance.playhead,
+                theme: instance.theme.clone(),
+                alpha: *alpha,
+            },
+        }
+    }
+
+    #[cfg_attr(not(feature = "lottie"), allow(unused_variables))]
+    fn encode(&self, encoder: &mut Encoder) -> Scene {
+        let mut scene = Scene::new();
+        match self {
+            Stale::Text(text, font) => {
+                font.render(&mut scene, Affine::IDENTITY, &text.text, text.alignment);
+            }
+            #[cfg(feature = "lottie")]
+            Stale::Lottie(instance, composition, alpha) => {
+                encoder.velato_renderer.render(
+                    {
+                        instance
+                            .theme
+                            .as_ref()
+                            .map(|cs| cs.recolor(composition))
+                            .as_ref()
+                            .unwrap_or(composition)
+                    },
+                    instance.playhead,
+                    Affine::IDENTITY,
+                    *alpha as f64,
+                    &mut scene,
+                );
+            }
+        }
+        scene
+    }
+}
+
+impl EncodingCache {
+    /// The encoding of a text, once its font is loaded.
+    pub fn text(&self, entity: Entity) -> Option<&Scene> {
+        self.texts.get(&entity).map(|fragment| &fragment.scene)
+    }
+
+    /// The encoding of a Lottie asset at its current frame.
+    #[cfg(feature = "lottie")]
+    pub fn lottie(&self, entity: Entity) -> Option<&Scene> {
+        self.lotties.get(&entity).map(|fragment| &fragment.scene)
+    }
+}
+
+/// Re-encode the text and Lottie fragments whose source changed, in parallel
+/// on the [`ComputeTaskPool`], and drop those no longer extracted.
+pub fn update_encoding_cache(
+    mut cache: ResMut<EncodingCache>,
+    texts: Query<&ExtractedRenderText>,
+    fonts: Res<RenderAssets<VelloFont>>,
+    #[cfg(feature = "lottie")] assets: Query<&ExtractedRenderAsset>,
+    #[cfg(feature = "lottie")] vello_assets: Res<RenderAssets<VelloAsset>>,
+) {
+    let cache = &mut *cache;
+    let mut stale = Vec::new();
+
+    let mut extracted = EntityHashSet::default();
+    for text in texts.iter() {
+        // Text isn't drawn until its font is loaded
+        let Some(font) = fonts.get(&text.font) else {
+            continue;
+        };
+        extracted.insert(text.entity);
+        if !cache
+            .texts
+            .get(&text.entity)
+            .is_some_and(|fragment| fragment.source.is_text(text))
+        {
+            stale.push((text.entity, Stale::Text(text, font)));
+        }
+    }
+    cache.texts.retain(|entity, _| extracted.contains(entity));
+
+    #[cfg(feature = "lottie")]
+    {
+        let mut extracted = EntityHashSet::default();
+        for instance in assets.iter() {
+            let Some(VelloAsset {
+                file: crate::VectorFile::Lottie(composition),
+                alpha,
+                ..
+            }) = vello_assets.get(&instance.asset)
+            else {
+                continue;
+            };
+            let alpha = alpha * instance.opacity;
+            extracted.insert(instance.entity);
+            if !cache
+                .lotties
+                .get(&instance.entity)
+                .is_some_and(|fragment| fragment.source.is_lottie(instance, composition, alpha))
+            {
+                stale.push((instance.entity, Stale::Lottie(instance, composition, alpha)));
+            }
+        }
+        cache.lotties.retain(|entity, _| extracted.contains(entity));
+    }
+
+    if stale.is_empty() {
+        return;
+    }
+
+    // Split the work evenly across threads, each task with its own encoder
+    let pool = ComputeTaskPool::get();
+    let chunk_size = stale.len().div_ceil(pool.thread_num().max(1));
+    let chunks = stale.len().div_ceil(chunk_size);
+    if cache.encoders.len() < chunks {
+        cache.encoders.resize_with(chunks, Default::default);
+    }
+    let encoded = pool.scope(|scope| {
+        for (chunk, encoder) in stale.chunks(chunk_size).zip(cache.encoders.iter_mut()) {
+            scope.spawn(async move {
+                chunk
+                    .iter()
+                    .map(|(entity, stale)| (*entity, stale.source(), stale.encode(encoder)))
+                    .collect::<Vec<_>>()
+            });
+        }
+    });
+
+    for (entity, source, scene) in encoded.into_iter().flatten() {
+        let fragments = match source {
+            Source::Text { .. } => &mut cache.texts,
+            #[cfg(feature = "lottie")]
+            Source::Lottie { .. } => &mut cache.lotties,
+        };
+        fragments.insert(entity, Fragment { source, scene });
+    }
+}
@@ -0,0 +1,26 @@
+use crate::VelloAsset;
+use bevy::prelude::*;
+use vello::kurbo;
+
+/// Clips the Vello content of this entity and its descendants.
+///
+/// Shapes are in this entity's local space, the same space [`VelloScene`](crate::VelloScene)
+/// content is drawn in: centered on the entity's transform, with y down. Clips
+/// follow the entity's [`CoordinateSpace`](crate::CoordinateSpace), and nest
+/// with the clips of ancestors.
+#[derive(Component, Clone)]
+pub enum VelloClip {
+    Rect(kurbo::Rect),
+    RoundedRect(kurbo::RoundedRect),
+    Path(kurbo::BezPath),
+    /// Use the alpha of another vector asset, centered on this entity, as a mask.
+    /// Content stays unclipped until the asset is loaded. Lottie masks are
+    /// drawn at the frame of this entity's `Playhead`, or
+    /// their first frame without one.
+    Mask(Handle<VelloAsset>),
+}
+
+/// The [`VelloClip`]s applying to an entity's Vello content, outermost first.
+/// Kept up to date by the plugin.
+#[derive(Component, Clone, Debug, Default, PartialEq, Eq, Deref)]
+pub struct InVelloClips(pub Vec<Entity>);
@@ -1,13 +1,24 @@
+use super::blend::VelloBlend;
+use super::clip::{InVelloClips, VelloClip};
+use super::layer::InVelloLayer;
+use super::opacity::InheritedVelloOpacity;
+use super::painter::VelloPaint;
+use super::shadow::VelloShadow;
+use super::texture::VelloTexture;
 use super::z_function::ZFunction;
 use crate::text::VelloTextAlignment;
 use crate::{CoordinateSpace, VelloAsset, VelloAssetAlignment, VelloFont, VelloScene, VelloText};
 use bevy::prelude::*;
+use bevy::render::primitives::Aabb;
+use bevy::render::view::RenderLayers;
 use bevy::render::{extract_component::ExtractComponent, Extract};
-use bevy::window::PrimaryWindow;
 
 #[derive(Component, Clone)]
 pub struct ExtractedRenderAsset {
-    pub asset: VelloAsset,
+    /// The main world entity this was extracted from.
+    pub entity: Entity,
+    /// The asset, looked up in `RenderAssets<VelloAsset>` once prepared.
+    pub asset: Handle<VelloAsset>,
     pub alignment: VelloAssetAlignment,
     pub transform: GlobalTransform,
     pub z_function: ZFunction,
@@ -15,21 +26,43 @@ pub struct ExtractedRenderAsset {
     pub theme: Option<crate::Theme>,
     pub render_mode: CoordinateSpace,
     pub playhead: f64,
-    pub alpha: f32,
     pub ui_node: Option<Node>,
+    pub render_layers: RenderLayers,
+    /// The [`VelloTexture`] entity this is rendered into, instead of camera canvases.
+    pub texture: Option<Entity>,
+    /// The [`VelloLayer`](super::VelloLayer) this is rendered into, if any.
+    pub layer: Option<Entity>,
+    /// The [`VelloClip`] entities clipping this, outermost first.
+    pub clips: Vec<Entity>,
+    pub blend: Option<VelloBlend>,
+    /// The inherited [`VelloOpacity`](super::VelloOpacity).
+    pub opacity: f32,
+    pub shadow: Option<VelloShadow>,
 }
 
-#[cfg(feature = "svg")]
-pub fn extract_svg_instances(
+/// Extract the instances of assets that are encoded already: SVG assets and
+/// [`VelloShape`](crate::shape::VelloShape)s. Lottie assets are extracted
+/// with their playhead instead.
+pub fn extract_encoded_instances(
     mut commands: Commands,
     query_vectors: Extract<
         Query<(
+            Entity,
             &Handle<VelloAsset>,
             &VelloAssetAlignment,
             &CoordinateSpace,
             &ZFunction,
             &GlobalTransform,
             Option<&Node>,
+            Option<&RenderLayers>,
+            Has<VelloTexture>,
+            (
+                Option<&InVelloLayer>,
+                Option<&InVelloClips>,
+                Option<&VelloBlend>,
+                Option<&InheritedVelloOpacity>,
+                Option<&VelloShadow>,
+            ),
             &ViewVisibility,
             &InheritedVisibility,
         )>,
@@ -37,27 +70,33 @@ pub fn extract_svg_instances(
     assets: Extract<Res<Assets<VelloAsset>>>,
 ) {
     for (
+        entity,
         vello_vector_handle,
         alignment,
         coord_space,
         z_function,
         transform,
         ui_node,
+        render_layers,
+        has_texture,
+        (layer, clips, blend, opacity, shadow),
         view_visibility,
         inherited_visibility,
     ) in query_vectors.iter()
     {
-        if let Some(
-            asset @ VelloAsset {
-                file: _file @ crate::VectorFile::Svg(_),
-                alpha,
-                ..
-            },
-        ) = assets.get(vello_vector_handle)
-        {
-            if view_visibility.get() && inherited_visibility.get() {
+        if let Some(asset) = assets.get(vello_vector_handle) {
+            match asset.file {
+                #[cfg(feature = "svg")]
+                crate::VectorFile::Svg(_) => {}
+                crate::VectorFile::Scene(_) => {}
+                #[cfg(feature = "lottie")]
+                crate::VectorFile::Lottie(_) => continue,
+            }
+            // Textures aren't seen by any view, so only their own visibility counts
+            if (view_visibility.get() || has_texture) && inherited_visibility.get() {
                 commands.spawn(ExtractedRenderAsset {
-                    asset: asset.to_owned(),
+                    entity,
+                    asset: vello_vector_handle.clone(),
                     transform: *transform,
                     alignment: *alignment,
                     z_function: *z_function,
@@ -65,8 +104,14 @@ pub fn extract_svg_instances(
                     theme: None,
                     render_mode: *coord_space,
                     playhead: 0.0,
-                    alpha: *alpha,
                     ui_node: ui_node.cloned(),
+                    render_layers: render_layers.copied().unwrap_or_default(),
+                    texture: has_texture.then_some(entity),
+                    layer: layer.map(|layer| layer.0),
+                    clips: clips.map(|clips| clips.0.clone()).unwrap_or_default(),
+                    blend: blend.copied(),
+                    opacity: opacity.map_or(1.0, |opacity| opacity.0),
+                    shadow: shadow.copied(),
                 });
             }
         }
@@ -78,6 +123,7 @@ pub fn extract_lottie_instances(
     mut commands: Commands,
     query_vectors: Extract<
         Query<(
+            Entity,
             &Handle<VelloAsset>,
             &VelloAssetAlignment,
             &CoordinateSpace,
@@ -86,6 +132,15 @@ pub fn extract_lottie_instances(
             &crate::Playhead,
             Option<&crate::Theme>,
             Option<&Node>,
+            Option<&RenderLayers>,
+            Has<VelloTexture>,
+            (
+                Option<&InVelloLayer>,
+                Option<&InVelloClips>,
+                Option<&VelloBlend>,
+                Option<&InheritedVelloOpacity>,
+                Option<&VelloShadow>,
+            ),
             &ViewVisibility,
             &InheritedVisibility,
         )>,
@@ -93,6 +148,7 @@ pub fn extract_lottie_instances(
     assets: Extract<Res<Assets<VelloAsset>>>,
 ) {
     for (
+        entity,
         vello_vector_handle,
         alignment,
         coord_space,
@@ -101,30 +157,38 @@ pub fn extract_lottie_instances(
         playhead,
         theme,
         ui_node,
+        render_layers,
+        has_texture,
+        (layer, clips, blend, opacity, shadow),
         view_visibility,
         inherited_visibility,
     ) in query_vectors.iter()
     {
-        if let Some(
-            asset @ VelloAsset {
-                file: _file @ crate::VectorFile::Lottie(_),
-                alpha,
-                ..
-            },
-        ) = assets.get(vello_vector_handle)
+        if let Some(VelloAsset {
+            file: crate::VectorFile::Lottie(_),
+            ..
+        }) = assets.get(vello_vector_handle)
         {
-            if view_visibility.get() && inherited_visibility.get() {
+            // Textures aren't seen by any view, so only their own visibility counts
+            if (view_visibility.get() || has_texture) && inherited_visibility.get() {
                 let playhead = playhead.frame();
                 commands.spawn(ExtractedRenderAsset {
-                    asset: asset.to_owned(),
+                    entity,
+                    asset: vello_vector_handle.clone(),
                     transform: *transform,
                     alignment: *alignment,
                     z_function: *z_function,
                     theme: theme.cloned(),
                     render_mode: *coord_space,
                     playhead,
-                    alpha: *alpha,
                     ui_node: ui_node.cloned(),
+                    render_layers: render_layers.copied().unwrap_or_default(),
+                    texture: has_texture.then_some(entity),
+                    layer: layer.map(|layer| layer.0),
+                    clips: clips.map(|clips| clips.0.clone()).unwrap_or_default(),
+                    blend: blend.copied(),
+                    opacity: opacity.map_or(1.0, |opacity| opacity.0),
+                    shadow: shadow.copied(),
                 });
             }
         }
@@ -133,76 +197,220 @@ pub fn extract_lottie_instances(
 
 #[derive(Component, Clone)]
 pub struct ExtractedRenderScene {
+    /// The main world entity this was extracted from, `None` for canvases
+    /// painted by a [`VelloPainter`](super::VelloPainter).
+    pub entity: Option<Entity>,
     pub scene: VelloScene,
     pub transform: GlobalTransform,
     pub render_mode: CoordinateSpace,
+    pub render_layers: RenderLayers,
+    /// The [`VelloTexture`] entity this is rendered into, instead of camera canvases.
+    pub texture: Option<Entity>,
+    /// The [`VelloLayer`](super::VelloLayer) this is rendered into, if any.
+    pub layer: Option<Entity>,
+    /// The [`VelloClip`] entities clipping this, outermost first.
+    pub clips: Vec<Entity>,
+    pub blend: Option<VelloBlend>,
+    /// The inherited [`VelloOpacity`](super::VelloOpacity).
+    pub opacity: f32,
+    pub shadow: Option<VelloShadow>,
+    /// The bounds of world space content, used to bound its blend and
+    /// opacity layers.
+    pub aabb: Option<Aabb>,
 }
 
 pub fn scene_instances(
     mut commands: Commands,
     query_scenes: Extract<
         Query<(
+            Entity,
             &VelloScene,
             &CoordinateSpace,
             &GlobalTransform,
+            Option<&RenderLayers>,
+            Has<VelloTexture>,
+            (
+                Option<&InVelloLayer>,
+                Option<&InVelloClips>,
+                Option<&VelloBlend>,
+                Option<&InheritedVelloOpacity>,
+                Option<&VelloShadow>,
+                Option<&Aabb>,
+            ),
             &ViewVisibility,
             &InheritedVisibility,
         )>,
     >,
 ) {
-    for (scene, coord_space, transform, view_visibility, inherited_visibility) in
-        query_scenes.iter()
+    for (
+        entity,
+        scene,
+        coord_space,
+        transform,
+        render_layers,
+        has_texture,
+        (layer, clips, blend, opacity, shadow, aabb),
+        view_visibility,
+        inherited_visibility,
+    ) in query_scenes.iter()
     {
-        if view_visibility.get() && inherited_visibility.get() {
+        // Textures aren't seen by any view, so only their own visibility counts
+        if (view_visibility.get() || has_texture) && inherited_visibility.get() {
             commands.spawn(ExtractedRenderScene {
+                entity: Some(entity),
                 transform: *transform,
                 render_mode: *coord_space,
                 scene: scene.clone(),
+                render_layers: render_layers.copied().unwrap_or_default(),
+                texture: has_texture.then_some(entity),
+                layer: layer.map(|layer| layer.0),
+                clips: clips.map(|clips| clips.0.clone()).unwrap_or_default(),
+                blend: blend.copied(),
+                opacity: opacity.map_or(1.0, |opacity| opacity.0),
+                shadow: shadow.copied(),
+                // Screen space content isn't culled, so its Aabb isn't kept
+                aabb: aabb
+                    .copied()
+                    .filter(|_| *coord_space == CoordinateSpace::WorldSpace),
             });
         }
     }
 }
 
+/// Extract the canvases painted by [`VelloPainter`](super::VelloPainter)s this
+/// frame, drawn like [`VelloScene`]s at their z.
+pub fn extract_painted_scenes(mut commands: Commands, paint: Extract<Res<VelloPaint>>) {
+    for canvas in paint.canvases.iter() {
+        commands.spawn(ExtractedRenderScene {
+            entity: None,
+            scene: canvas.scene.clone(),
+            transform: GlobalTransform::from_translation(Vec3::Z * canvas.z),
+            render_mode: canvas.space,
+            render_layers: canvas.render_layers,
+            texture: None,
+            layer: None,
+            clips: Vec::new(),
+            blend: None,
+            opacity: 1.0,
+            shadow: None,
+            aabb: None,
+        });
+    }
+}
+
 #[derive(Component, Clone)]
 pub struct ExtractedRenderText {
+    /// The main world entity this was extracted from.
+    pub entity: Entity,
     pub font: Handle<VelloFont>,
     pub text: VelloText,
     pub alignment: VelloTextAlignment,
     pub transform: GlobalTransform,
     pub render_mode: CoordinateSpace,
+    pub render_layers: RenderLayers,
+    /// The [`VelloTexture`] entity this is rendered into, instead of camera canvases.
+    pub texture: Option<Entity>,
+    /// The [`VelloLayer`](super::VelloLayer) this is rendered into, if any.
+    pub layer: Option<Entity>,
+    /// The [`VelloClip`] entities clipping this, outermost first.
+    pub clips: Vec<Entity>,
+    pub blend: Option<VelloBlend>,
+    /// The inherited [`VelloOpacity`](super::VelloOpacity).
+    pub opacity: f32,
+    pub shadow: Option<VelloShadow>,
+    /// The bounds of world space content, used to bound its blend and
+    /// opacity layers.
+    pub aabb: Option<Aabb>,
 }
 
-impl ExtractComponent for ExtractedRenderText {
-    type QueryData = (
-        &'static Handle<VelloFont>,
-        &'static VelloText,
-        &'static VelloTextAlignment,
-        &'static GlobalTransform,
-        &'static CoordinateSpace,
-    );
-
-    type QueryFilter = ();
-
-    type Out = Self;
-
-    fn extract_component(
-        (vello_font_handle, text, alignment, transform, render_mode): bevy::ecs::query::QueryItem<
-            '_,
-            Self::QueryData,
-        >,
-    ) -> Option<Self> {
-        Some(Self {
-            font: vello_font_handle.clone(),
-            text: text.clone(),
-            alignment: *alignment,
-            transform: *transform,
-            render_mode: *render_mode,
-        })
+pub fn extract_text_instances(
+    mut commands: Commands,
+    query_texts: Extract<
+        Query<(
+            Entity,
+            &Handle<VelloFont>,
+            &VelloText,
+            &VelloTextAlignment,
+            &GlobalTransform,
+            &CoordinateSpace,
+            Option<&RenderLayers>,
+            Has<VelloTexture>,
+            (
+                Option<&InVelloLayer>,
+                Option<&InVelloClips>,
+                Option<&VelloBlend>,
+                Option<&InheritedVelloOpacity>,
+                Option<&VelloShadow>,
+                Option<&Aabb>,
+            ),
+            &ViewVisibility,
+            &InheritedVisibility,
+        )>,
+    >,
+) {
+    for (
+        entity,
+        vello_font_handle,
+        text,
+        alignment,
+        transform,
+        render_mode,
+        render_layers,
+        has_texture,
+        (layer, clips, blend, opacity, shadow, aabb),
+        view_visibility,
+        inherited_visibility,
+    ) in query_texts.iter()
+    {
+        // Textures aren't seen by any view, so only their own visibility counts
+        if (view_visibility.get() || has_texture) && inherited_visibility.get() {
+            commands.spawn(ExtractedRenderText {
+                entity,
+                font: vello_font_handle.clone(),
+                text: text.clone(),
+                alignment: *alignment,
+                transform: *transform,
+                render_mode: *render_mode,
+                render_layers: render_layers.copied().unwrap_or_default(),
+                texture: has_texture.then_some(entity),
+                layer: layer.map(|layer| layer.0),
+                clips: clips.map(|clips| clips.0.clone()).unwrap_or_default(),
+                blend: blend.copied(),
+                opacity: opacity.map_or(1.0, |opacity| opacity.0),
+                shadow: shadow.copied(),
+                aabb: aabb.copied(),
+            });
+        }
     }
 }
 
-#[derive(Component, Default)]
-pub struct SSRenderTarget(pub Handle<Image>);
+/// The canvas a camera renders Vello content onto. Every `Camera2d` gets
+/// exactly one, plus one for each [`VelloLayer`](super::VelloLayer).
+#[derive(Component, Clone)]
+pub struct SSRenderTarget {
+    /// The texture Vello renders into.
+    pub image: Handle<Image>,
+    /// The camera this canvas belongs to.
+    pub camera: Entity,
+    /// The [`VelloLayer`](super::VelloLayer) drawn onto this canvas, or `None`
+    /// for content outside of any layer.
+    pub layer: Option<Entity>,
+    /// The texture [`VelloShadow`]s are blurred into, or a transparent
+    /// placeholder while none of the canvas' content has a shadow.
+    pub shadows: Handle<Image>,
+    /// The radius the canvas is blurred by after it's drawn, in physical
+    /// pixels, from its [`VelloPostProcessing`](super::VelloPostProcessing).
+    pub blur: u32,
+    /// The radius the canvas is blurred into `glow` by for its bloom, in
+    /// physical pixels, or `None` without bloom.
+    pub bloom: Option<u32>,
+    /// The texture the canvas is blurred into for its bloom, or a transparent
+    /// placeholder without bloom.
+    pub glow: Handle<Image>,
+    /// For HDR cameras, the `Rgba16Float` texture `image` is converted into
+    /// and sampled from, in linear color.
+    pub float: Option<Handle<Image>>,
+}
 
 impl ExtractComponent for SSRenderTarget {
     type QueryData = &'static SSRenderTarget;
@@ -214,21 +422,113 @@ impl ExtractComponent for SSRenderTarget {
     fn extract_component(
         ss_render_target: bevy::ecs::query::QueryItem<'_, Self::QueryData>,
     ) -> Option<Self> {
-        Some(Self(ss_render_target.0.clone()))
+        Some(ss_render_target.clone())
     }
 }
 
-#[derive(Resource)]
+/// The scale factor of a camera's render target. Windows report their own
+/// scale factor, while images are always `1.0`.
+#[derive(Component, Clone, Copy)]
 pub struct ExtractedPixelScale(pub f32);
 
 pub fn extract_pixel_scale(
-    mut pixel_scale: ResMut<ExtractedPixelScale>,
-    windows: Extract<Query<&Window, With<PrimaryWindow>>>,
+    mut commands: Commands,
+    cameras: Extract<Query<(Entity, &Camera), With<Camera2d>>>,
 ) {
-    let scale_factor = windows
-        .get_single()
-        .map(|window| window.resolution.scale_factor())
-        .unwrap_or(1.0);
+    for (entity, camera) in cameras.iter() {
+        let scale_factor = camera.target_scaling_factor().unwrap_or(1.0);
+        commands
+            .get_or_spawn(entity)
+            .insert(ExtractedPixelScale(scale_factor));
+    }
+}
+
+/// A [`VelloTexture`] in the render world, on its main world entity.
+#[derive(Component, Clone)]
+pub struct ExtractedVelloTexture {
+    pub image: Handle<Image>,
+    pub size: UVec2,
+    /// Places the content on the texture.
+    pub transform: GlobalTransform,
+}
+
+pub fn extract_vello_textures(
+    mut commands: Commands,
+    textures: Extract<
+        Query<(
+            Entity,
+            &VelloTexture,
+            &GlobalTransform,
+            &InheritedVisibility,
+        )>,
+    >,
+) {
+    for (entity, texture, transform, inherited_visibility) in textures.iter() {
+        if !inherited_visibility.get() || texture.size.x == 0 || texture.size.y == 0 {
+            continue;
+        }
+        commands.get_or_spawn(entity).insert(ExtractedVelloTexture {
+            image: texture.image().clone(),
+            size: texture.size,
+            transform: *transform,
+        });
+    }
+}
+
+/// A [`VelloClip`] in the render world, on its main world entity.
+#[derive(Component, Clone)]
+pub struct ExtractedVelloClip {
+    pub shape: ExtractedClipShape,
+    pub transform: GlobalTransform,
+    pub render_mode: CoordinateSpace,
+}
+
+#[derive(Clone)]
+pub enum ExtractedClipShape {
+    Rect(vello::kurbo::Rect),
+    RoundedRect(vello::kurbo::RoundedRect),
+    Path(vello::kurbo::BezPath),
+    Mask {
+        asset: Handle<VelloAsset>,
+        /// The frame Lottie masks are drawn at.
+        #[cfg(feature = "lottie")]
+        playhead: f64,
+    },
+}
 
-    pixel_scale.0 = scale_factor;
+pub fn extract_vello_clips(
+    mut commands: Commands,
+    clips: Extract<
+        Query<(
+            Entity,
+            &VelloClip,
+            &GlobalTransform,
+            Option<&CoordinateSpace>,
+            &InheritedVisibility,
+        )>,
+    >,
+    #[cfg(feature = "lottie")] playheads: Extract<Query<&crate::Playhead>>,
+) {
+    for (entity, clip, transform, coord_space, inherited_visibility) in clips.iter() {
+        if !inherited_visibility.get() {
+            continue;
+        }
+        let shape = match clip {
+            VelloClip::Rect(rect) => ExtractedClipShape::Rect(*rect),
+            VelloClip::RoundedRect(rect) => ExtractedClipShape::RoundedRect(*rect),
+            VelloClip::Path(path) => ExtractedClipShape::Path(path.clone()),
+            VelloClip::Mask(handle) => ExtractedClipShape::Mask {
+                asset: handle.clone(),
+                #[cfg(feature = "lottie")]
+                playhead: playheads
+                    .get(entity)
+                    .map_or(0.0, |playhead| playhead.frame()),
+            },
+        };
+        commands.get_or_spawn(entity).insert(ExtractedVelloClip {
+            shape,
+            transform: *transform,
+            render_mode: coord_space.copied().unwrap_or_default(),
+        });
+    }
 }
@@ -0,0 +1,17 @@
+use bevy::prelude::*;
+
+/// Renders the Vello content of this entity and its descendants onto a canvas
+/// of its own, drawn at this entity's Z like any other 2D mesh. This lets Vello
+/// content sort against sprites and meshes, which otherwise are all drawn
+/// either in front of or behind the single canvas of each camera.
+///
+/// Content belongs to the nearest [`VelloLayer`] among its ancestors. Vello
+/// z-ordering only applies within a layer.
+#[derive(Component, Default, Clone, Copy, Debug, Reflect)]
+#[reflect(Component)]
+pub struct VelloLayer;
+
+/// The [`VelloLayer`] an entity's Vello content is rendered into. Kept up to
+/// date by the plugin.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InVelloLayer(pub Entity);
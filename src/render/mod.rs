@@ -3,29 +3,96 @@
 use bevy::prelude::*;
 use bevy::render::mesh::MeshVertexBufferLayout;
 use bevy::render::render_resource::{
-    AsBindGroup, RenderPipelineDescriptor, ShaderRef, SpecializedMeshPipelineError,
+    AsBindGroup, BlendState, RenderPipelineDescriptor, ShaderRef, SpecializedMeshPipelineError,
     VertexBufferLayout, VertexFormat, VertexStepMode,
 };
+use bevy::render::renderer::RenderDevice;
 use bevy::sprite::{Material2d, Material2dKey};
+use std::sync::{Mutex, PoisonError};
 
+mod blend;
+mod blur;
+mod cache;
+mod clip;
 mod extract;
+mod layer;
+mod opacity;
+mod painter;
+mod picking;
 mod plugin;
+mod post_process;
 mod prepare;
+mod readback;
+mod redraw;
+mod settings;
+mod shadow;
 mod systems;
+mod texture;
 mod z_function;
 
+pub use blend::VelloBlend;
+pub use blur::MAX_BLUR_RADIUS;
+pub use clip::{InVelloClips, VelloClip};
+pub use layer::{InVelloLayer, VelloLayer};
+pub use opacity::{InheritedVelloOpacity, VelloOpacity};
+pub use painter::{PaintCanvas, VelloPainter};
 pub use plugin::VelloRenderPlugin;
+pub use post_process::{VelloBloom, VelloColorGrading, VelloPostProcessing, VelloVignette};
+pub use readback::ImageReadback;
+pub use redraw::VelloRedraw;
+#[cfg(not(target_arch = "wasm32"))]
+pub use settings::fallback_adapter_render_creation;
+pub use settings::VelloRenderSettings;
+pub use shadow::VelloShadow;
+pub use texture::VelloTexture;
 pub use z_function::ZFunction;
 
+pub(crate) use prepare::{screen_space_asset_matrix, vello_affine_coeffs};
+
 /// A handle to the screen space render target shader.
 pub const SSRT_SHADER_HANDLE: Handle<Shader> = Handle::weak_from_u128(2314894693238056781);
 
 /// A canvas material, with a shader that samples a texture with view-independent UV coordinates.
+///
+/// Canvases are drawn into the camera's own target before tonemapping, so with
+/// `Camera { hdr: true }` they are tonemapped and bloomed with the rest of the
+/// scene, and [`VelloPostProcessing`] exposure can take them above 1.0. HDR
+/// cameras sample their canvases from an `Rgba16Float` texture of linear
+/// color, converted from the 8-bit texture Vello renders into.
 #[derive(AsBindGroup, TypePath, Asset, Clone)]
+#[bind_group_data(VelloCanvasMaterialKey)]
 pub struct VelloCanvasMaterial {
     #[texture(0)]
     #[sampler(1)]
     pub texture: Handle<Image>,
+    /// `texture` is a float texture of linear premultiplied color, with the
+    /// shadows drawn in, for HDR cameras.
+    pub(crate) float: bool,
+    /// The [`VelloPostProcessing`] applied to this canvas.
+    #[uniform(2)]
+    pub(crate) post_processing: post_process::PostProcessingUniform,
+    /// The [`VelloShadow`]s of this canvas' content, drawn beneath it.
+    #[texture(3)]
+    #[sampler(4)]
+    pub(crate) shadows: Handle<Image>,
+    /// The canvas blurred by its [`VelloBloom`] radius.
+    #[texture(5)]
+    #[sampler(6)]
+    pub(crate) glow: Handle<Image>,
+}
+
+/// Specializes the canvas shader for float canvases.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VelloCanvasMaterialKey {
+    float: bool,
+}
+
+impl From<&VelloCanvasMaterial> for VelloCanvasMaterialKey {
+    fn from(material: &VelloCanvasMaterial) -> Self {
+        Self {
+            float: material.float,
+        }
+    }
 }
 
 impl Material2d for VelloCanvasMaterial {
@@ -40,7 +107,7 @@ impl Material2d for VelloCanvasMaterial {
     fn specialize(
         descriptor: &mut RenderPipelineDescriptor,
         _layout: &MeshVertexBufferLayout,
-        _key: Material2dKey<Self>,
+        key: Material2dKey<Self>,
     ) -> Result<(), SpecializedMeshPipelineError> {
         let formats = vec![
             // Position
@@ -53,12 +120,116 @@ impl Material2d for VelloCanvasMaterial {
 
         descriptor.vertex.buffers = vec![vertex_layout];
 
+        // The shader outputs premultiplied alpha
+        if let Some(target) = descriptor
+            .fragment
+            .as_mut()
+            .and_then(|fragment| fragment.targets.first_mut())
+            .and_then(Option::as_mut)
+        {
+            target.blend = Some(BlendState::PREMULTIPLIED_ALPHA_BLENDING);
+        }
+        if key.bind_group_data.float {
+            if let Some(fragment) = descriptor.fragment.as_mut() {
+                fragment.shader_defs.push("FLOAT_CANVAS".into());
+            }
+        }
+
         Ok(())
     }
 }
 
 #[derive(Deref, DerefMut)]
-pub struct VelloRenderer(vello::Renderer);
+pub struct VelloRenderer {
+    #[deref]
+    renderer: vello::Renderer,
+    /// The settings this renderer was created with.
+    settings: VelloRenderSettings,
+}
+
+impl VelloRenderer {
+    /// Create a renderer, falling back to Vello's CPU shaders when the GPU
+    /// pipelines cannot be created on this device.
+    pub fn new(device: &RenderDevice, settings: &VelloRenderSettings) -> vello::Result<Self> {
+        let options = settings.renderer_options();
+        let renderer = vello::Renderer::new(device.wgpu_device(), options).or_else(|err| {
+            if settings.use_cpu {
+                return Err(err);
+            }
+            warn!("Could not create the Vello GPU renderer ({err}), falling back to CPU shaders");
+            vello::Renderer::new(
+                device.wgpu_device(),
+                vello::RendererOptions {
+                    use_cpu: true,
+                    ..settings.renderer_options()
+                },
+            )
+        })?;
+        Ok(Self {
+            renderer,
+            settings: settings.clone(),
+        })
+    }
+}
+
+/// The [`VelloRenderer`] shared by the render systems, created on first use
+/// and rebuilt when the [`VelloRenderSettings`] it depends on change.
+#[derive(Resource, Default)]
+pub struct SharedVelloRenderer {
+    /// Behind a mutex because `vello::Renderer` isn't `Sync`. Systems take the
+    /// resource mutably, so it's never actually locked.
+    renderer: Option<Mutex<VelloRenderer>>,
+    /// Settings that failed to create a renderer, so they aren't retried
+    /// every frame.
+    failed_settings: Option<VelloRenderSettings>,
+}
+
+impl SharedVelloRenderer {
+    /// The renderer for `settings`, created or rebuilt if needed. `None` if
+    /// no renderer can be created with these settings.
+    pub fn get_or_create(
+        &mut self,
+        device: &RenderDevice,
+        settings: &VelloRenderSettings,
+    ) -> Option<&mut VelloRenderer> {
+        // Rebuild the renderer if the settings it was created with are stale
+        if self
+            .renderer()
+            .is_some_and(|renderer| renderer.settings.requires_rebuild(settings))
+        {
+            debug!("Vello render settings changed, rebuilding renderer");
+            self.renderer.take();
+        }
+        if self.renderer.is_none() {
+            // Don't retry every frame with settings that already failed
+            if self
+                .failed_settings
+                .as_ref()
+                .is_some_and(|failed| !failed.requires_rebuild(settings))
+            {
+                return None;
+            }
+            match VelloRenderer::new(device, settings) {
+                Ok(renderer) => {
+                    self.renderer.replace(Mutex::new(renderer));
+                    self.failed_settings.take();
+                }
+                Err(err) => {
+                    error!("Could not create the Vello renderer, nothing will be rendered: {err}");
+                    self.failed_settings.replace(settings.clone());
+                    return None;
+                }
+            }
+        }
+        self.renderer()
+    }
+
+    fn renderer(&mut self) -> Option<&mut VelloRenderer> {
+        self.renderer
+            .as_mut()
+            .map(|renderer| renderer.get_mut().unwrap_or_else(PoisonError::into_inner))
+    }
+}
 
 #[derive(Resource, Deref, DerefMut)]
 #[cfg(feature = "lottie")]
@@ -0,0 +1,19 @@
+use bevy::prelude::*;
+
+/// The opacity of this entity's Vello content and all of its descendants',
+/// from 0 to 1. Opacities multiply down the hierarchy, so fading a parent
+/// fades its children too.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Deref, DerefMut, Reflect)]
+#[reflect(Component)]
+pub struct VelloOpacity(pub f32);
+
+impl Default for VelloOpacity {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// The product of the [`VelloOpacity`] of an entity and its ancestors. Kept up
+/// to date by the plugin.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Deref)]
+pub struct InheritedVelloOpacity(pub f32);
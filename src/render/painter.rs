@@ -0,0 +1,185 @@
+use super::VelloRedraw;
+use crate::text::VelloTextAlignment;
+use crate::{CoordinateSpace, VelloAsset, VelloFont, VelloScene, VelloText};
+use bevy::ecs::system::{Deferred, SystemBuffer, SystemMeta, SystemParam};
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use vello::kurbo::Affine;
+use vello::Scene;
+
+/// Draw Vello content for the current frame only, from any system.
+///
+/// Like [`Gizmos`], everything painted is drawn for one frame and then
+/// discarded, and any number of systems can paint in parallel. Each
+/// [`canvas`](Self::canvas) is sorted by its z with all other Vello content.
+///
+/// ```ignore
+/// fn draw_marquee(mut painter: VelloPainter) {
+///     let mut canvas = painter.canvas(CoordinateSpace::WorldSpace, 10.0);
+///     canvas.stroke(&kurbo::Stroke::new(2.0), Affine::IDENTITY, Color::WHITE, None, &rect);
+/// }
+/// ```
+#[derive(SystemParam)]
+pub struct VelloPainter<'w, 's> {
+    buffer: Deferred<'s, PaintBuffer>,
+    fonts: Res<'w, Assets<VelloFont>>,
+    assets: Res<'w, Assets<VelloAsset>>,
+}
+
+impl VelloPainter<'_, '_> {
+    /// Start a canvas drawn at `z` in `space`.
+    ///
+    /// World space canvases are drawn like a [`VelloScene`] at the world
+    /// origin, with y down. Screen space canvases are in logical pixels from
+    /// the top left of the viewport, and are drawn above all world space
+    /// content.
+    pub fn canvas(&mut self, space: CoordinateSpace, z: f32) -> PaintCanvas<'_> {
+        let buffer = &mut *self.buffer;
+        buffer.canvases.push(PaintedCanvas {
+            scene: Scene::new(),
+            space,
+            z,
+            render_layers: RenderLayers::default(),
+        });
+        PaintCanvas {
+            canvas: buffer
+                .canvases
+                .last_mut()
+                .expect("a canvas was just pushed"),
+            fonts: &self.fonts,
+            assets: &self.assets,
+            #[cfg(feature = "lottie")]
+            velato_renderer: &mut buffer.velato_renderer,
+        }
+    }
+}
+
+/// A scene painted for one frame by a [`VelloPainter`].
+///
+/// Dereferences to the [`Scene`], for fills, strokes, glyph runs and layers.
+pub struct PaintCanvas<'a> {
+    canvas: &'a mut PaintedCanvas,
+    fonts: &'a Assets<VelloFont>,
+    assets: &'a Assets<VelloAsset>,
+    #[cfg(feature = "lottie")]
+    velato_renderer: &'a mut super::VelatoRenderer,
+}
+
+impl std::ops::Deref for PaintCanvas<'_> {
+    type Target = Scene;
+
+    fn deref(&self) -> &Self::Target {
+        &self.canvas.scene
+    }
+}
+
+impl std::ops::DerefMut for PaintCanvas<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.canvas.scene
+    }
+}
+
+impl PaintCanvas<'_> {
+    /// Draw this canvas for the cameras on these layers only.
+    pub fn set_render_layers(&mut self, render_layers: RenderLayers) -> &mut Self {
+        self.canvas.render_layers = render_layers;
+        self
+    }
+
+    /// Draw text with the font, if it is loaded.
+    pub fn text(
+        &mut self,
+        font: &Handle<VelloFont>,
+        text: &VelloText,
+        alignment: VelloTextAlignment,
+        transform: Affine,
+    ) -> &mut Self {
+        if let Some(font) = self.fonts.get(font) {
+            font.render(&mut self.canvas.scene, transform, text, alignment);
+        }
+        self
+    }
+
+    /// Draw a vector asset centered on `transform`, if it is loaded. Lottie
+    /// assets are drawn at their first frame.
+    pub fn asset(&mut self, asset: &Handle<VelloAsset>, transform: Affine) -> &mut Self {
+        let Some(asset) = self.assets.get(asset) else {
+            return self;
+        };
+        let transform =
+            transform * Affine::translate((-asset.width as f64 / 2.0, -asset.height as f64 / 2.0));
+        match &asset.file {
+            #[cfg(feature = "svg")]
+            crate::VectorFile::Svg(scene) => self.canvas.scene.append(scene, Some(transform)),
+            crate::VectorFile::Scene(scene) => self.canvas.scene.append(scene, Some(transform)),
+            #[cfg(feature = "lottie")]
+            crate::VectorFile::Lottie(composition) => self.velato_renderer.render(
+                composition,
+                composition.frames.start,
+                transform,
+                asset.alpha as f64,
+                &mut self.canvas.scene,
+            ),
+        }
+        self
+    }
+}
+
+struct PaintedCanvas {
+    scene: Scene,
+    space: CoordinateSpace,
+    z: f32,
+    render_layers: RenderLayers,
+}
+
+/// The canvases a system painted, until they're applied to [`VelloPaint`].
+#[derive(Default)]
+pub struct PaintBuffer {
+    canvases: Vec<PaintedCanvas>,
+    #[cfg(feature = "lottie")]
+    velato_renderer: super::VelatoRenderer,
+}
+
+impl SystemBuffer for PaintBuffer {
+    fn apply(&mut self, _system_meta: &SystemMeta, world: &mut World) {
+        if self.canvases.is_empty() {
+            return;
+        }
+        let mut paint = world.resource_mut::<VelloPaint>();
+        paint
+            .canvases
+            .extend(self.canvases.drain(..).map(|canvas| PaintedScene {
+                scene: canvas.scene.into(),
+                space: canvas.space,
+                z: canvas.z,
+                render_layers: canvas.render_layers,
+            }));
+        if let Some(mut redraw) = world.get_resource_mut::<VelloRedraw>() {
+            redraw.request();
+        }
+    }
+}
+
+/// A canvas painted this frame, shared with the render world.
+pub(crate) struct PaintedScene {
+    pub scene: VelloScene,
+    pub space: CoordinateSpace,
+    pub z: f32,
+    pub render_layers: RenderLayers,
+}
+
+/// Everything painted by [`VelloPainter`]s this frame.
+#[derive(Resource, Default)]
+pub struct VelloPaint {
+    pub(crate) canvases: Vec<PaintedScene>,
+}
+
+/// Discard the previous frame's paint.
+pub fn clear_vello_paint(mut paint: ResMut<VelloPaint>, mut redraw: ResMut<VelloRedraw>) {
+    if paint.canvases.is_empty() {
+        return;
+    }
+    paint.canvases.clear();
+    // Erase what was painted
+    redraw.request();
+}
@@ -0,0 +1,441 @@
+//! Picking [`VelloPickable`] content by rendering it under the cursor.
+
+use super::cache::EncodingCache;
+use super::extract::{
+    ExtractedRenderAsset, ExtractedRenderScene, ExtractedRenderText, ExtractedVelloClip,
+};
+use super::prepare::{PreparedAffines, PreparedClipAffines, PreparedZIndex};
+use super::redraw::VelloRedraw;
+use super::systems::{encode_item, item_clips, pop_clip, push_clip, RenderItem};
+use super::{SharedVelloRenderer, VelloLayer, VelloRenderSettings};
+use crate::picking::{PickPointer, VelloPickable, VelloPicking};
+use crate::{CoordinateSpace, VelloAsset, VelloFont};
+use bevy::ecs::entity::{EntityHashMap, EntityHashSet};
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssets;
+use bevy::render::render_resource::{
+    Buffer, BufferAsyncError, BufferDescriptor, BufferUsages, CommandEncoderDescriptor, Extent3d,
+    ImageCopyBuffer, ImageDataLayout, Maintain, MapMode, Texture, TextureDescriptor,
+    TextureDimension, TextureFormat, TextureUsages, TextureView,
+};
+use bevy::render::renderer::{RenderDevice, RenderQueue};
+use bevy::render::Extract;
+use std::sync::{Arc, Mutex};
+use vello::kurbo::{self, Affine};
+use vello::peniko::{Color, Fill, Mix};
+use vello::{RenderParams, Scene};
+
+/// Every candidate is drawn into a pixel of its own, in rows this wide.
+const ROW_PIXELS: u32 = 64;
+
+/// The alpha a candidate must cover its pixel with to be hit, so the
+/// antialiased edges around holes and outlines are not.
+const HIT_ALPHA: u8 = 128;
+
+/// The [`VelloPickable`] main world entities, and the Z of every
+/// [`VelloLayer`] to order their canvases.
+#[derive(Resource, Default)]
+pub struct ExtractedPickables {
+    entities: EntityHashSet,
+    layers: EntityHashMap<f32>,
+    /// Whether the entities or layers changed since the last extraction.
+    changed: bool,
+}
+
+pub fn extract_pickables(
+    mut pickables: ResMut<ExtractedPickables>,
+    entities: Extract<Query<Entity, With<VelloPickable>>>,
+    layers: Extract<Query<(Entity, &GlobalTransform), With<VelloLayer>>>,
+) {
+    let entities: EntityHashSet = entities.iter().collect();
+    let layers: EntityHashMap<f32> = layers
+        .iter()
+        .map(|(entity, transform)| (entity, transform.translation().z))
+        .collect();
+    pickables.changed = pickables.entities != entities || pickables.layers != layers;
+    pickables.entities = entities;
+    pickables.layers = layers;
+}
+
+/// The texture picks are rendered to, and the pick being read back.
+#[derive(Resource, Default)]
+pub struct PickReadback {
+    target: Option<PickTarget>,
+    pending: Option<PendingPick>,
+    /// Where the last pick was made.
+    picked: Option<PickPointer>,
+    /// Whether content changed since the last pick.
+    stale: bool,
+}
+
+impl PickReadback {
+    /// Whether content must be picked again at `pointer`, once it moved or
+    /// the content changed.
+    fn needs_pick(&self, pointer: PickPointer) -> bool {
+        self.stale || self.picked != Some(pointer)
+    }
+}
+
+struct PickTarget {
+    texture: Texture,
+    view: TextureView,
+    rows: u32,
+}
+
+/// A pick copied into a buffer, until the buffer is mapped.
+struct PendingPick {
+    /// The picked entities, bottom-most first, one per pixel.
+    candidates: Vec<Entity>,
+    buffer: Buffer,
+    mapped: Arc<Mutex<Option<Result<(), BufferAsyncError>>>>,
+}
+
+impl PendingPick {
+    /// The candidates drawn at the cursor, top-most first.
+    fn hits(&self) -> Vec<Entity> {
+        let row_bytes = RenderDevice::align_copy_bytes_per_row((ROW_PIXELS * 4) as usize);
+        let data = self.buffer.slice(..).get_mapped_range();
+        let hits = covered_candidates(&self.candidates, &data, row_bytes);
+        drop(data);
+        self.buffer.unmap();
+        hits
+    }
+}
+
+/// The candidates that covered their pixel in the read back `data`, top-most
+/// first.
+fn covered_candidates(candidates: &[Entity], data: &[u8], row_bytes: usize) -> Vec<Entity> {
+    candidates
+        .iter()
+        .enumerate()
+        .rev()
+        .filter(|(i, _)| {
+            let (column, row) = (*i as u32 % ROW_PIXELS, *i as u32 / ROW_PIXELS);
+            let alpha = row as usize * row_bytes + column as usize * 4 + 3;
+            data[alpha] >= HIT_ALPHA
+        })
+        .map(|(_, entity)| *entity)
+        .collect()
+}
+
+/// Render the silhouette of every pickable item under the cursor into a pixel
+/// of its own, in the order the items are drawn, and read back which ones
+/// covered their pixel. Content is only picked again once the pointer moved or
+/// the content changed.
+#[allow(clippy::too_many_arguments)]
+pub fn pick_vello_content(
+    picking: Res<VelloPicking>,
+    pickables: Res<ExtractedPickables>,
+    redraw: Res<VelloRedraw>,
+    mut readback: ResMut<PickReadback>,
+    query_render_vectors: Query<(&PreparedAffines, &PreparedZIndex, &ExtractedRenderAsset)>,
+    query_render_scenes: Query<(&PreparedAffines, &ExtractedRenderScene)>,
+    query_render_texts: Query<(&PreparedAffines, &ExtractedRenderText)>,
+    query_clips: Query<(&ExtractedVelloClip, &PreparedClipAffines)>,
+    vello_assets: Res<RenderAssets<VelloAsset>>,
+    fonts: Res<RenderAssets<VelloFont>>,
+    device: Res<RenderDevice>,
+    queue: Res<RenderQueue>,
+    settings: Res<VelloRenderSettings>,
+    mut vello_renderer: ResMut<SharedVelloRenderer>,
+    encoding_cache: Res<EncodingCache>,
+    #[cfg(feature = "lottie")] mut velato_renderer: ResMut<super::VelatoRenderer>,
+) {
+    let readback = &mut *readback;
+    // Changes while a pick is in flight are picked after it
+    readback.stale |= redraw.is_needed() || pickables.changed;
+
+    // Only one pick is in flight, the next starts once it's read back
+    if let Some(pending) = readback.pending.take() {
+        device.poll(Maintain::Poll);
+        let mapped = pending
+            .mapped
+            .lock()
+            .ok()
+            .and_then(|mut mapped| mapped.take());
+        match mapped {
+            None => {
+                readback.pending = Some(pending);
+                return;
+            }
+            Some(Ok(())) => picking.publish(pending.hits()),
+            Some(Err(err)) => error!("Could not read back Vello picks: {err}"),
+        }
+    }
+
+    let Some(pointer) = picking.pointer() else {
+        readback.picked = None;
+        return;
+    };
+    if !readback.needs_pick(pointer) {
+        return;
+    }
+
+    // Layer canvases are drawn at their layer's Z, others just behind 0
+    let canvas_z = |layer: Option<Entity>| {
+        layer.map_or(-0.001, |layer| {
+            pickables.layers.get(&layer).copied().unwrap_or_default()
+        })
+    };
+    let mut candidates: Vec<(f32, CoordinateSpace, f32, Entity, Affine, RenderItem)> =
+        query_render_vectors
+            .iter()
+            .filter_map(|(a, &b, c)| {
+                if !pickables.entities.contains(&c.entity) {
+                    return None;
+                }
+                let affine = a.get(&pointer.camera)?;
+                let asset = vello_assets.get(&c.asset)?;
+                Some((
+                    canvas_z(c.layer),
+                    c.render_mode,
+                    *b,
+                    c.entity,
+                    *affine,
+                    RenderItem::Asset(c, asset),
+                ))
+            })
+            .collect();
+    candidates.extend(query_render_scenes.iter().filter_map(|(a, b)| {
+        let entity = b
+            .entity
+            .filter(|entity| pickables.entities.contains(entity))?;
+        let affine = a.get(&pointer.camera)?;
+        Some((
+            canvas_z(b.layer),
+            b.render_mode,
+            b.transform.translation().z,
+            entity,
+            *affine,
+            RenderItem::Scene(b),
+        ))
+    }));
+    candidates.extend(query_render_texts.iter().filter_map(|(a, b)| {
+        if !pickables.entities.contains(&b.entity) {
+            return None;
+        }
+        let affine = a.get(&pointer.camera)?;
+        Some((
+            canvas_z(b.layer),
+            b.render_mode,
+            b.transform.translation().z,
+            b.entity,
+            *affine,
+            RenderItem::Text(b),
+        ))
+    }));
+    if candidates.is_empty() {
+        picking.publish(Vec::new());
+        readback.picked = Some(pointer);
+        readback.stale = false;
+        return;
+    }
+
+    // Sort like `render_scene`, after ordering the canvases themselves
+    candidates.sort_by(|(a_canvas, a_mode, a_z, ..), (b_canvas, b_mode, b_z, ..)| {
+        let canvas = a_canvas.total_cmp(b_canvas);
+        let z_index = a_z.partial_cmp(b_z).unwrap_or(std::cmp::Ordering::Equal);
+        canvas.then(a_mode.cmp(b_mode)).then(z_index)
+    });
+
+    let mut scene = Scene::new();
+    for (i, (.., affine, item)) in candidates.iter().enumerate() {
+        let (column, row) = (i as u32 % ROW_PIXELS, i as u32 / ROW_PIXELS);
+        let pixel = kurbo::Rect::new(
+            column as f64,
+            row as f64,
+            column as f64 + 1.0,
+            row as f64 + 1.0,
+        );
+        // Move the cursor onto the center of the item's pixel
+        let cursor = kurbo::Point::new(pointer.position.x as f64, pointer.position.y as f64);
+        let offset = Affine::translate(pixel.center() - cursor);
+
+        scene.push_layer(Mix::Clip, 1.0, Affine::IDENTITY, &pixel);
+        let clips = item_clips(item, &query_clips, pointer.camera);
+        for (clip, clip_affine) in clips.iter() {
+            push_clip(&mut scene, clip, offset * *clip_affine, &vello_assets);
+        }
+        match item {
+            RenderItem::Text(text) => {
+                if let Some(font) = fonts.get(&text.font) {
+                    for glyph in font.glyph_boxes(&text.text, text.alignment) {
+                        scene.fill(Fill::NonZero, offset * *affine, Color::WHITE, None, &glyph);
+                    }
+                }
+            }
+            item => encode_item(
+                &mut scene,
+                item,
+                offset * *affine,
+                &encoding_cache,
+                #[cfg(feature = "lottie")]
+                &mut velato_renderer,
+            ),
+        }
+        for (clip, clip_affine) in clips.iter().rev() {
+            pop_clip(
+                &mut scene,
+                clip,
+                offset * *clip_affine,
+                &vello_assets,
+                #[cfg(feature = "lottie")]
+                &mut velato_renderer,
+            );
+        }
+        scene.pop_layer();
+    }
+
+    let Some(renderer) = vello_renderer.get_or_create(&device, &settings) else {
+        return;
+    };
+    let rows = (candidates.len() as u32).div_ceil(ROW_PIXELS);
+    // Grow the texture once there are more candidates than pixels
+    if readback.target.as_ref().map(|target| target.rows) < Some(rows) {
+        let texture = device.create_texture(&TextureDescriptor {
+            label: Some("vello_picking_texture"),
+            size: Extent3d {
+                width: ROW_PIXELS,
+                height: rows,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Rgba8Unorm,
+            usage: TextureUsages::STORAGE_BINDING | TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&default());
+        readback.target = Some(PickTarget {
+            texture,
+            view,
+            rows,
+        });
+    }
+    let Some(target) = readback.target.as_ref() else {
+        return;
+    };
+
+    if let Err(err) = renderer.render_to_texture(
+        device.wgpu_device(),
+        &queue,
+        &scene,
+        &target.view,
+        &RenderParams {
+            base_color: Color::TRANSPARENT,
+            width: ROW_PIXELS,
+            height: target.rows,
+            antialiasing_method: settings.antialiasing,
+        },
+    ) {
+        error!("Vello failed to render picks: {err}");
+        return;
+    }
+
+    // Only copy the rows drawn this time
+    let row_bytes = RenderDevice::align_copy_bytes_per_row((ROW_PIXELS * 4) as usize);
+    let buffer = device.create_buffer(&BufferDescriptor {
+        label: Some("vello_picking_buffer"),
+        size: (row_bytes * rows as usize) as u64,
+        usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+    let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+        label: Some("vello_picking_readback"),
+    });
+    encoder.copy_texture_to_buffer(
+        target.texture.as_image_copy(),
+        ImageCopyBuffer {
+            buffer: &buffer,
+            layout: ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(row_bytes as u32),
+                rows_per_image: None,
+            },
+        },
+        Extent3d {
+            width: ROW_PIXELS,
+            height: rows,
+            depth_or_array_layers: 1,
+        },
+    );
+    queue.submit([encoder.finish()]);
+
+    let mapped: Arc<Mutex<Option<Result<(), BufferAsyncError>>>> = default();
+    let mapped_callback = mapped.clone();
+    buffer.slice(..).map_async(MapMode::Read, move |result| {
+        if let Ok(mut mapped) = mapped_callback.lock() {
+            mapped.replace(result);
+        }
+    });
+    readback.picked = Some(pointer);
+    readback.stale = false;
+    readback.pending = Some(PendingPick {
+        candidates: candidates
+            .into_iter()
+            .map(|(_, _, _, entity, ..)| entity)
+            .collect(),
+        buffer,
+        mapped,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROW_BYTES: usize = (ROW_PIXELS * 4) as usize;
+
+    /// Read back data with each candidate's pixel covered by `alphas`.
+    fn data(alphas: &[u8]) -> Vec<u8> {
+        let mut data = vec![0; ROW_BYTES * 2];
+        for (i, alpha) in alphas.iter().enumerate() {
+            data[i * 4 + 3] = *alpha;
+        }
+        data
+    }
+
+    #[test]
+    fn holes_are_not_hit() {
+        let (rect, donut) = (Entity::from_raw(1), Entity::from_raw(2));
+        // The cursor in the donut's hole, over a rect
+        let hits = covered_candidates(&[rect, donut], &data(&[255, 0]), ROW_BYTES);
+        assert_eq!(hits, vec![rect]);
+        // On the antialiased edge of the hole
+        let hits = covered_candidates(&[rect, donut], &data(&[255, 100]), ROW_BYTES);
+        assert_eq!(hits, vec![rect]);
+        // On the donut itself, top-most first
+        let hits = covered_candidates(&[rect, donut], &data(&[255, 200]), ROW_BYTES);
+        assert_eq!(hits, vec![donut, rect]);
+    }
+
+    #[test]
+    fn candidates_wrap_onto_rows() {
+        let candidates: Vec<Entity> = (0..ROW_PIXELS + 1).map(Entity::from_raw).collect();
+        let mut data = data(&[]);
+        data[ROW_BYTES + 3] = 255;
+        let hits = covered_candidates(&candidates, &data, ROW_BYTES);
+        assert_eq!(hits, vec![Entity::from_raw(ROW_PIXELS)]);
+    }
+
+    #[test]
+    fn picks_again_on_pointer_or_content_changes() {
+        let pointer = PickPointer {
+            camera: Entity::from_raw(0),
+            position: Vec2::new(10.0, 10.0),
+        };
+        let mut readback = PickReadback::default();
+        assert!(readback.needs_pick(pointer));
+
+        readback.picked = Some(pointer);
+        assert!(!readback.needs_pick(pointer));
+        assert!(readback.needs_pick(PickPointer {
+            position: Vec2::new(11.0, 10.0),
+            ..pointer
+        }));
+
+        readback.stale = true;
+        assert!(readback.needs_pick(pointer));
+    }
+}
@@ -1,13 +1,16 @@
-use super::extract::{self, ExtractedPixelScale, SSRenderTarget};
-use super::{prepare, systems};
-use crate::render::extract::ExtractedRenderText;
-use crate::render::SSRT_SHADER_HANDLE;
-use crate::{VelloCanvasMaterial, VelloFont};
+use super::extract::{self, SSRenderTarget};
+use super::{
+    blur, cache, painter, picking, post_process, prepare, readback, redraw, shadow, systems,
+};
+use crate::render::{VelloPostProcessing, VelloRenderSettings, SSRT_SHADER_HANDLE};
+use crate::{VelloAsset, VelloCanvasMaterial, VelloFont};
 use bevy::asset::load_internal_asset;
 use bevy::prelude::*;
 use bevy::render::extract_component::ExtractComponentPlugin;
+use bevy::render::extract_resource::ExtractResourcePlugin;
 use bevy::render::render_asset::RenderAssetPlugin;
-use bevy::render::renderer::RenderDevice;
+use bevy::render::renderer::{render_system, RenderDevice};
+use bevy::render::view::VisibilitySystems;
 use bevy::render::{Render, RenderApp, RenderSet};
 use bevy::sprite::Material2dPlugin;
 
@@ -21,25 +24,42 @@ impl Plugin for VelloRenderPlugin {
             "../../shaders/vello_ss_rendertarget.wgsl",
             Shader::from_wgsl
         );
+        if let Some(mut images) = app.world.get_resource_mut::<Assets<Image>>() {
+            images.insert(
+                shadow::SHADOWS_PLACEHOLDER_HANDLE,
+                blur::placeholder_image(),
+            );
+            images.insert(
+                post_process::GLOW_PLACEHOLDER_HANDLE,
+                blur::placeholder_image(),
+            );
+        }
 
         let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
             return;
         };
 
-        #[cfg(feature = "svg")]
-        render_app.add_systems(ExtractSchedule, extract::extract_svg_instances);
+        render_app.add_systems(ExtractSchedule, extract::extract_encoded_instances);
         #[cfg(feature = "lottie")]
         render_app
             .init_resource::<super::VelatoRenderer>()
             .add_systems(ExtractSchedule, extract::extract_lottie_instances);
 
         render_app
-            .insert_resource(ExtractedPixelScale(1.0))
+            .init_resource::<cache::EncodingCache>()
+            .init_resource::<super::SharedVelloRenderer>()
+            .init_resource::<picking::ExtractedPickables>()
+            .init_resource::<picking::PickReadback>()
             .add_systems(
                 ExtractSchedule,
                 (
                     extract::extract_pixel_scale.in_set(RenderSet::ExtractCommands),
+                    extract::extract_vello_textures.in_set(RenderSet::ExtractCommands),
+                    extract::extract_vello_clips,
                     extract::scene_instances,
+                    extract::extract_text_instances,
+                    extract::extract_painted_scenes,
+                    picking::extract_pickables,
                 ),
             )
             .add_systems(
@@ -48,26 +68,81 @@ impl Plugin for VelloRenderPlugin {
                     prepare::prepare_vector_affines,
                     prepare::prepare_scene_affines,
                     prepare::prepare_text_affines,
+                    prepare::prepare_clip_affines,
+                    cache::update_encoding_cache.run_if(redraw::vello_redraw_needed),
                 )
                     .in_set(RenderSet::Prepare),
             )
             .add_systems(
                 Render,
-                systems::render_scene
-                    .in_set(RenderSet::Render)
-                    .run_if(resource_exists::<RenderDevice>),
+                (
+                    systems::render_scene
+                        .in_set(RenderSet::Render)
+                        .before(render_system)
+                        .run_if(resource_exists::<RenderDevice>)
+                        .run_if(redraw::vello_redraw_needed),
+                    picking::pick_vello_content
+                        .in_set(RenderSet::Render)
+                        .after(systems::render_scene)
+                        .run_if(resource_exists::<RenderDevice>),
+                    readback::readback_images
+                        .in_set(RenderSet::Render)
+                        .after(render_system),
+                ),
             );
 
-        app.add_plugins((
-            Material2dPlugin::<VelloCanvasMaterial>::default(),
-            ExtractComponentPlugin::<ExtractedRenderText>::default(),
-            ExtractComponentPlugin::<SSRenderTarget>::default(),
-            RenderAssetPlugin::<VelloFont>::default(),
-        ))
-        .add_systems(Startup, systems::setup_ss_rendertarget)
-        .add_systems(
-            Update,
-            (systems::resize_rendertargets, systems::clear_when_empty),
-        );
+        app.init_resource::<VelloRenderSettings>()
+            .init_resource::<VelloPostProcessing>()
+            .init_resource::<redraw::VelloRedraw>()
+            .init_resource::<painter::VelloPaint>()
+            .add_plugins((
+                Material2dPlugin::<VelloCanvasMaterial>::default(),
+                ExtractComponentPlugin::<SSRenderTarget>::default(),
+                ExtractComponentPlugin::<readback::ImageReadback>::default(),
+                ExtractResourcePlugin::<VelloRenderSettings>::default(),
+                ExtractResourcePlugin::<redraw::VelloRedraw>::default(),
+                ExtractResourcePlugin::<crate::picking::VelloPicking>::default(),
+                RenderAssetPlugin::<VelloFont>::default(),
+                RenderAssetPlugin::<VelloAsset>::default(),
+            ))
+            .add_systems(
+                Update,
+                (
+                    systems::setup_ss_rendertargets,
+                    systems::despawn_orphaned_rendertargets,
+                    systems::sync_layer_canvases,
+                    systems::update_canvas_post_processing,
+                    systems::update_canvas_shadows,
+                    systems::propagate_vello_hierarchy,
+                    systems::resize_rendertargets,
+                    systems::resize_vello_textures,
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                (
+                    (
+                        systems::calculate_asset_bounds,
+                        systems::calculate_text_bounds,
+                    )
+                        .in_set(VisibilitySystems::CalculateBounds),
+                    systems::hide_foreign_rendertargets.after(VisibilitySystems::CheckVisibility),
+                    redraw::detect_vello_changes.after(VisibilitySystems::CheckVisibility),
+                ),
+            )
+            .add_systems(
+                First,
+                (redraw::reset_vello_redraw, painter::clear_vello_paint).chain(),
+            );
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        // Needs the `RenderDevice`, which is only there once plugins are built
+        if render_app.world.contains_resource::<RenderDevice>() {
+            render_app.init_resource::<blur::BlurRenderer>();
+        }
     }
 }
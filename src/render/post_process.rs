@@ -0,0 +1,160 @@
+use super::blur::{blur_target_image, MAX_BLUR_RADIUS};
+use bevy::prelude::*;
+use bevy::render::render_resource::ShaderType;
+
+/// The glow texture of canvases without bloom.
+pub const GLOW_PLACEHOLDER_HANDLE: Handle<Image> = Handle::weak_from_u128(5829104736251849301);
+
+/// The image a canvas is blurred into for its bloom.
+pub(crate) fn glow_image(size: UVec2) -> Image {
+    blur_target_image("vello_glow", size)
+}
+
+/// Post-processing applied to the Vello canvas before it is composited with
+/// the rest of the frame.
+///
+/// As a resource, this applies to every canvas. Added as a component to a
+/// camera or a [`VelloLayer`](super::VelloLayer), it overrides the resource
+/// for that camera's canvases, or for that layer's.
+#[derive(Resource, Component, Clone, Debug, Default, PartialEq)]
+pub struct VelloPostProcessing {
+    /// The radius of a gaussian blur over the whole canvas, in physical
+    /// pixels, rounded and clamped to [`MAX_BLUR_RADIUS`]. `0.0` disables it.
+    pub blur: f32,
+    pub bloom: VelloBloom,
+    pub color_grading: VelloColorGrading,
+    pub vignette: VelloVignette,
+}
+
+/// A glow around the bright parts of the canvas.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VelloBloom {
+    /// How strongly the glow is added. `0.0` disables it.
+    pub intensity: f32,
+    /// The luminance above which content glows.
+    pub threshold: f32,
+    /// The radius of the glow, in physical pixels, rounded and clamped to
+    /// [`MAX_BLUR_RADIUS`].
+    pub radius: f32,
+}
+
+impl VelloPostProcessing {
+    /// The radius the canvas is blurred by, in whole pixels.
+    pub(crate) fn blur_radius(&self) -> u32 {
+        blur_radius(self.blur)
+    }
+
+    /// The radius the canvas is blurred by for its bloom, in whole pixels, or
+    /// `None` without bloom.
+    pub(crate) fn bloom_radius(&self) -> Option<u32> {
+        (self.bloom.intensity > 0.0).then(|| blur_radius(self.bloom.radius))
+    }
+}
+
+fn blur_radius(radius: f32) -> u32 {
+    radius.round().clamp(0.0, MAX_BLUR_RADIUS as f32) as u32
+}
+
+impl Default for VelloBloom {
+    fn default() -> Self {
+        Self {
+            intensity: 0.0,
+            threshold: 0.8,
+            radius: 16.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct VelloColorGrading {
+    /// Exposure adjustment, in stops. On HDR cameras, positive exposure takes
+    /// the canvas above 1.0, into Bevy's bloom and tonemapping.
+    pub exposure: f32,
+    /// Contrast around middle grey, where `1.0` is unchanged.
+    pub contrast: f32,
+    /// Saturation, where `0.0` is greyscale and `1.0` is unchanged.
+    pub saturation: f32,
+    /// A color multiplied with the canvas.
+    pub tint: Color,
+}
+
+impl Default for VelloColorGrading {
+    fn default() -> Self {
+        Self {
+            exposure: 0.0,
+            contrast: 1.0,
+            saturation: 1.0,
+            tint: Color::WHITE,
+        }
+    }
+}
+
+/// Darkens the canvas towards the edges of the viewport.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VelloVignette {
+    /// How dark the edges get, from 0 to 1. `0.0` disables it.
+    pub intensity: f32,
+    /// Where the darkening ends, from the center (`0.0`) to the corners (`1.0`).
+    pub radius: f32,
+    /// How far inwards from `radius` the darkening fades in.
+    pub smoothness: f32,
+}
+
+impl Default for VelloVignette {
+    fn default() -> Self {
+        Self {
+            intensity: 0.0,
+            radius: 1.0,
+            smoothness: 0.5,
+        }
+    }
+}
+
+pub(crate) use uniform::PostProcessingUniform;
+
+// The layout checks generated by `ShaderType` trip `dead_code` on newer compilers
+#[allow(dead_code)]
+mod uniform {
+    use super::*;
+
+    /// [`VelloPostProcessing`] as laid out in the canvas shader.
+    #[derive(Clone, Copy, Debug, PartialEq, ShaderType)]
+    pub struct PostProcessingUniform {
+        blur_radius: f32,
+        bloom_intensity: f32,
+        bloom_threshold: f32,
+        exposure: f32,
+        contrast: f32,
+        saturation: f32,
+        vignette_intensity: f32,
+        vignette_radius: f32,
+        vignette_smoothness: f32,
+        tint: Vec4,
+    }
+
+    impl Default for PostProcessingUniform {
+        fn default() -> Self {
+            Self::from(&VelloPostProcessing::default())
+        }
+    }
+
+    impl From<&VelloPostProcessing> for PostProcessingUniform {
+        fn from(settings: &VelloPostProcessing) -> Self {
+            Self {
+                // The canvas is blurred before it's drawn, the shader only
+                // needs to know whether it was
+                blur_radius: settings.blur_radius() as f32,
+                bloom_intensity: settings.bloom.intensity.max(0.0),
+                bloom_threshold: settings.bloom.threshold,
+                exposure: settings.color_grading.exposure,
+                contrast: settings.color_grading.contrast,
+                saturation: settings.color_grading.saturation,
+                vignette_intensity: settings.vignette.intensity.clamp(0.0, 1.0),
+                vignette_radius: settings.vignette.radius,
+                // `smoothstep` is undefined when both edges meet
+                vignette_smoothness: settings.vignette.smoothness.max(0.0001),
+                tint: Vec4::from_array(settings.color_grading.tint.as_linear_rgba_f32()),
+            }
+        }
+    }
+}
@@ -1,14 +1,24 @@
 use super::extract::{
     ExtractedPixelScale, ExtractedRenderAsset, ExtractedRenderScene, ExtractedRenderText,
+    ExtractedVelloClip, ExtractedVelloTexture,
 };
-use crate::CoordinateSpace;
+use crate::{CoordinateSpace, VelloAsset};
+use bevy::ecs::entity::EntityHashMap;
 use bevy::prelude::*;
 use bevy::render::camera::ExtractedCamera;
-use bevy::render::view::ExtractedView;
+use bevy::render::render_asset::RenderAssets;
+use bevy::render::view::{ExtractedView, RenderLayers};
 use vello::kurbo::Affine;
 
-#[derive(Component, Copy, Clone, Deref, DerefMut)]
-pub struct PreparedAffine(Affine);
+/// The affine of a render instance for each view (camera) it is rendered by,
+/// keyed by the view entity.
+#[derive(Component, Clone, Default, Deref, DerefMut)]
+pub struct PreparedAffines(EntityHashMap<Affine>);
+
+/// The affine of a [`VelloClip`](super::VelloClip) for each view and texture
+/// it applies in, keyed like [`PreparedAffines`].
+#[derive(Component, Clone, Default, Deref, DerefMut)]
+pub struct PreparedClipAffines(EntityHashMap<Affine>);
 
 #[derive(Component, Copy, Clone, Deref, DerefMut)]
 pub struct PreparedTransform(GlobalTransform);
@@ -26,16 +36,30 @@ pub trait PrepareRenderInstance {
         world_transform: GlobalTransform,
         pixel_scale: f32,
         viewport_size: UVec2,
-    ) -> PreparedAffine;
+    ) -> Affine;
+}
+
+/// An [`ExtractedRenderAsset`] with its prepared [`VelloAsset`].
+struct AssetInstance<'a> {
+    instance: &'a ExtractedRenderAsset,
+    asset: &'a VelloAsset,
 }
 
-impl PrepareRenderInstance for ExtractedRenderAsset {
+impl PrepareRenderInstance for AssetInstance<'_> {
     fn z_index(&self, prepared_transform: GlobalTransform) -> PreparedZIndex {
-        PreparedZIndex(self.z_function.compute(&self.asset, &prepared_transform))
+        PreparedZIndex(
+            self.instance
+                .z_function
+                .compute(self.asset, &prepared_transform),
+        )
     }
 
     fn final_transform(&self) -> PreparedTransform {
-        PreparedTransform(self.alignment.compute(&self.asset, &self.transform))
+        PreparedTransform(
+            self.instance
+                .alignment
+                .compute(self.asset, &self.instance.transform),
+        )
     }
 
     fn scene_affine(
@@ -44,29 +68,16 @@ impl PrepareRenderInstance for ExtractedRenderAsset {
         world_transform: GlobalTransform,
         pixel_scale: f32,
         viewport_size: UVec2,
-    ) -> PreparedAffine {
-        let local_center_matrix = self.asset.local_transform_center.compute_matrix().inverse();
-
-        let raw_transform = match self.render_mode {
-            CoordinateSpace::ScreenSpace => {
-                let mut model_matrix = world_transform.compute_matrix().mul_scalar(pixel_scale);
-
-                let vector_size = Vec2::new(self.asset.width, self.asset.height);
-
-                // Make the screen space vector instance sized to fill the
-                // entire UI Node box if it's bundled with a Node
-                if let Some(node) = &self.ui_node {
-                    let fill_scale = node.size() / vector_size;
-                    model_matrix.x_axis.x *= fill_scale.x;
-                    model_matrix.y_axis.y *= fill_scale.y;
-                }
-
-                let mut local_center_matrix = local_center_matrix;
-                local_center_matrix.w_axis.y *= -1.0;
-                model_matrix * local_center_matrix
-            }
+    ) -> Affine {
+        let raw_transform = match self.instance.render_mode {
+            CoordinateSpace::ScreenSpace => screen_space_asset_matrix(
+                self.asset,
+                world_transform,
+                self.instance.ui_node.as_ref(),
+            )
+            .mul_scalar(pixel_scale),
             CoordinateSpace::WorldSpace => {
-                let local_matrix = local_center_matrix;
+                let local_matrix = self.asset.local_transform_center.compute_matrix().inverse();
 
                 let (pixels_x, pixels_y) = (viewport_size.x as f32, viewport_size.y as f32);
                 let ndc_to_pixels_matrix = Mat4::from_cols_array_2d(&[
@@ -93,6 +104,59 @@ impl PrepareRenderInstance for ExtractedRenderAsset {
             }
         };
 
+        Affine::new(vello_affine_coeffs(raw_transform).map(f64::from))
+    }
+}
+
+/// The matrix placing a screen space asset in logical pixels, from its
+/// aligned transform. Assets bundled with a UI `Node` are stretched to fill it.
+pub(crate) fn screen_space_asset_matrix(
+    asset: &VelloAsset,
+    world_transform: GlobalTransform,
+    node: Option<&Node>,
+) -> Mat4 {
+    let mut model_matrix = world_transform.compute_matrix();
+    if let Some(node) = node {
+        let fill_scale = node.size() / Vec2::new(asset.width, asset.height);
+        model_matrix.x_axis.x *= fill_scale.x;
+        model_matrix.y_axis.y *= fill_scale.y;
+    }
+
+    let mut local_center_matrix = asset.local_transform_center.compute_matrix().inverse();
+    local_center_matrix.w_axis.y *= -1.0;
+    model_matrix * local_center_matrix
+}
+
+/// The coefficients of the Vello affine for a transform in pixels, y up.
+pub(crate) fn vello_affine_coeffs(raw_transform: Mat4) -> [f32; 6] {
+    let transform: [f32; 16] = raw_transform.to_cols_array();
+
+    // | a c e |
+    // | b d f |
+    // | 0 0 1 |
+    [
+        transform[0],  // a
+        -transform[1], // b
+        -transform[4], // c
+        transform[5],  // d
+        transform[12], // e
+        transform[13], // f
+    ]
+}
+
+impl ExtractedVelloTexture {
+    /// The affine placing content on this texture. `content` maps the content's
+    /// local space (y-up) to the space of its transform, while the Vello
+    /// encoding itself is y-down.
+    fn affine(&self, content: Mat4) -> Affine {
+        let flip_y = Mat4::from_scale(Vec3::new(1.0, -1.0, 1.0));
+        let center = (self.size.as_vec2() / 2.0).extend(0.0);
+        let raw_transform = Mat4::from_translation(center)
+            * flip_y
+            * self.transform.compute_matrix()
+            * content
+            * flip_y;
+
         let transform: [f32; 16] = raw_transform.to_cols_array();
 
         // | a c e |
@@ -100,163 +164,391 @@ impl PrepareRenderInstance for ExtractedRenderAsset {
         // | 0 0 1 |
         let transform: [f64; 6] = [
             transform[0] as f64,  // a
-            -transform[1] as f64, // b
-            -transform[4] as f64, // c
+            transform[1] as f64,  // b
+            transform[4] as f64,  // c
             transform[5] as f64,  // d
             transform[12] as f64, // e
             transform[13] as f64, // f
         ];
 
-        PreparedAffine(Affine::new(transform))
+        Affine::new(transform)
     }
 }
 
 pub fn prepare_vector_affines(
     mut commands: Commands,
-    camera: Query<(&ExtractedCamera, &ExtractedView), With<Camera2d>>,
-    mut render_vectors: Query<(Entity, &ExtractedRenderAsset)>,
-    pixel_scale: Res<ExtractedPixelScale>,
+    views: Query<
+        (
+            Entity,
+            &ExtractedCamera,
+            &ExtractedView,
+            &ExtractedPixelScale,
+            Option<&RenderLayers>,
+        ),
+        With<Camera2d>,
+    >,
+    textures: Query<&ExtractedVelloTexture>,
+    render_vectors: Query<(Entity, &ExtractedRenderAsset)>,
+    assets: Res<RenderAssets<VelloAsset>>,
 ) {
-    let Ok((camera, view)) = camera.get_single() else {
-        return;
-    };
-    let viewport_size: UVec2 = camera.physical_viewport_size.unwrap();
-    for (entity, render_vector) in render_vectors.iter_mut() {
+    for (entity, instance) in render_vectors.iter() {
+        // Instances of assets that aren't prepared yet aren't rendered
+        let Some(asset) = assets.get(&instance.asset) else {
+            continue;
+        };
+        let render_vector = AssetInstance { instance, asset };
         // Prepare render data needed for the subsequent render system
         let final_transform = render_vector.final_transform();
-        let affine =
-            render_vector.scene_affine(view, *final_transform, pixel_scale.0, viewport_size);
         let z_index = render_vector.z_index(*final_transform);
 
+        let mut affines = PreparedAffines::default();
+        if let Some(target) = instance.texture {
+            if let Ok(texture) = textures.get(target) {
+                let content = instance
+                    .alignment
+                    .compute(asset, &GlobalTransform::IDENTITY)
+                    .compute_matrix()
+                    * asset.local_transform_center.compute_matrix().inverse();
+                affines.insert(target, texture.affine(content));
+            }
+            commands
+                .entity(entity)
+                .insert((affines, final_transform, z_index));
+            continue;
+        }
+        for (view_entity, camera, view, pixel_scale, view_layers) in views.iter() {
+            if !view_layers
+                .copied()
+                .unwrap_or_default()
+                .intersects(&instance.render_layers)
+            {
+                continue;
+            }
+            let Some(viewport_size) = camera.physical_viewport_size else {
+                continue;
+            };
+            let affine =
+                render_vector.scene_affine(view, *final_transform, pixel_scale.0, viewport_size);
+            affines.insert(view_entity, affine);
+        }
+
         commands
             .entity(entity)
-            .insert((affine, final_transform, z_index));
+            .insert((affines, final_transform, z_index));
     }
 }
 
 pub fn prepare_scene_affines(
     mut commands: Commands,
-    camera: Query<(&ExtractedCamera, &ExtractedView), With<Camera2d>>,
+    views: Query<
+        (
+            Entity,
+            &ExtractedCamera,
+            &ExtractedView,
+            &ExtractedPixelScale,
+            Option<&RenderLayers>,
+        ),
+        With<Camera2d>,
+    >,
+    textures: Query<&ExtractedVelloTexture>,
     mut render_vectors: Query<(Entity, &ExtractedRenderScene)>,
-    pixel_scale: Res<ExtractedPixelScale>,
 ) {
-    let Ok((camera, view)) = camera.get_single() else {
-        return;
-    };
-    let size_pixels: UVec2 = camera.physical_viewport_size.unwrap();
-    let (pixels_x, pixels_y) = (size_pixels.x as f32, size_pixels.y as f32);
     for (entity, render_vector) in render_vectors.iter_mut() {
-        let ndc_to_pixels_matrix = Mat4::from_cols_array_2d(&[
-            [pixels_x / 2.0, 0.0, 0.0, pixels_x / 2.0],
-            [0.0, pixels_y / 2.0, 0.0, pixels_y / 2.0],
-            [0.0, 0.0, 1.0, 0.0],
-            [0.0, 0.0, 0.0, 1.0],
-        ])
-        .transpose();
-
-        let world_transform = render_vector.transform;
-
-        let raw_transform = match render_vector.render_mode {
-            CoordinateSpace::ScreenSpace => {
-                let mut model_matrix = world_transform.compute_matrix().mul_scalar(pixel_scale.0);
-                model_matrix.w_axis.y *= -1.0;
-                model_matrix
+        let mut affines = PreparedAffines::default();
+        if let Some(target) = render_vector.texture {
+            if let Ok(texture) = textures.get(target) {
+                affines.insert(target, texture.affine(Mat4::IDENTITY));
             }
-            CoordinateSpace::WorldSpace => {
-                let mut model_matrix = world_transform.compute_matrix();
-                model_matrix.w_axis.y *= -1.0;
-
-                let (projection_mat, view_mat) = {
-                    let mut view_mat = view.transform.compute_matrix();
-                    view_mat.w_axis.y *= -1.0;
-
-                    (view.projection, view_mat)
-                };
-
-                let view_proj_matrix = projection_mat * view_mat.inverse();
-
-                ndc_to_pixels_matrix * view_proj_matrix * model_matrix
+            commands.entity(entity).insert(affines);
+            continue;
+        }
+        for (view_entity, camera, view, pixel_scale, view_layers) in views.iter() {
+            if !view_layers
+                .copied()
+                .unwrap_or_default()
+                .intersects(&render_vector.render_layers)
+            {
+                continue;
             }
-        };
+            let Some(size_pixels) = camera.physical_viewport_size else {
+                continue;
+            };
+            let affine = scene_affine(
+                render_vector.transform,
+                render_vector.render_mode,
+                view,
+                pixel_scale.0,
+                size_pixels,
+            );
+            affines.insert(view_entity, affine);
+        }
+
+        commands.entity(entity).insert(affines);
+    }
+}
 
-        let transform: [f32; 16] = raw_transform.to_cols_array();
+/// The affine of content drawn in the local space of `world_transform`, like
+/// [`VelloScene`](crate::VelloScene)s.
+fn scene_affine(
+    world_transform: GlobalTransform,
+    render_mode: CoordinateSpace,
+    view: &ExtractedView,
+    pixel_scale: f32,
+    size_pixels: UVec2,
+) -> Affine {
+    let (pixels_x, pixels_y) = (size_pixels.x as f32, size_pixels.y as f32);
+    let ndc_to_pixels_matrix = Mat4::from_cols_array_2d(&[
+        [pixels_x / 2.0, 0.0, 0.0, pixels_x / 2.0],
+        [0.0, pixels_y / 2.0, 0.0, pixels_y / 2.0],
+        [0.0, 0.0, 1.0, 0.0],
+        [0.0, 0.0, 0.0, 1.0],
+    ])
+    .transpose();
+
+    let raw_transform = match render_mode {
+        CoordinateSpace::ScreenSpace => {
+            let mut model_matrix = world_transform.compute_matrix().mul_scalar(pixel_scale);
+            model_matrix.w_axis.y *= -1.0;
+            model_matrix
+        }
+        CoordinateSpace::WorldSpace => {
+            let mut model_matrix = world_transform.compute_matrix();
+            model_matrix.w_axis.y *= -1.0;
+
+            let (projection_mat, view_mat) = {
+                let mut view_mat = view.transform.compute_matrix();
+                view_mat.w_axis.y *= -1.0;
+
+                (view.projection, view_mat)
+            };
+
+            let view_proj_matrix = projection_mat * view_mat.inverse();
+
+            ndc_to_pixels_matrix * view_proj_matrix * model_matrix
+        }
+    };
 
-        // | a c e |
-        // | b d f |
-        // | 0 0 1 |
-        let transform: [f64; 6] = [
-            transform[0] as f64,  // a
-            -transform[1] as f64, // b
-            -transform[4] as f64, // c
-            transform[5] as f64,  // d
-            transform[12] as f64, // e
-            transform[13] as f64, // f
-        ];
+    Affine::new(vello_affine_coeffs(raw_transform).map(f64::from))
+}
 
-        commands
-            .entity(entity)
-            .insert(PreparedAffine(Affine::new(transform)));
+pub fn prepare_clip_affines(
+    mut commands: Commands,
+    views: Query<
+        (
+            Entity,
+            &ExtractedCamera,
+            &ExtractedView,
+            &ExtractedPixelScale,
+        ),
+        With<Camera2d>,
+    >,
+    clips: Query<(Entity, &ExtractedVelloClip, Option<&ExtractedVelloTexture>)>,
+) {
+    for (entity, clip, texture) in clips.iter() {
+        let mut affines = PreparedClipAffines::default();
+        // Clips on a texture entity apply to its texture, keyed by that entity
+        if let Some(texture) = texture {
+            affines.insert(entity, texture.affine(Mat4::IDENTITY));
+        }
+        for (view_entity, camera, view, pixel_scale) in views.iter() {
+            let Some(size_pixels) = camera.physical_viewport_size else {
+                continue;
+            };
+            let affine = scene_affine(
+                clip.transform,
+                clip.render_mode,
+                view,
+                pixel_scale.0,
+                size_pixels,
+            );
+            affines.insert(view_entity, affine);
+        }
+
+        commands.entity(entity).insert(affines);
     }
 }
 
 pub fn prepare_text_affines(
     mut commands: Commands,
-    camera: Query<(&ExtractedCamera, &ExtractedView), With<Camera2d>>,
+    views: Query<
+        (
+            Entity,
+            &ExtractedCamera,
+            &ExtractedView,
+            &ExtractedPixelScale,
+            Option<&RenderLayers>,
+        ),
+        With<Camera2d>,
+    >,
+    textures: Query<&ExtractedVelloTexture>,
     render_texts: Query<(Entity, &ExtractedRenderText)>,
-    pixel_scale: Res<ExtractedPixelScale>,
 ) {
-    let Ok((camera, view)) = camera.get_single() else {
-        return;
-    };
-    let size_pixels: UVec2 = camera.physical_viewport_size.unwrap();
-    let (pixels_x, pixels_y) = (size_pixels.x as f32, size_pixels.y as f32);
     for (entity, render_text) in render_texts.iter() {
-        let ndc_to_pixels_matrix = Mat4::from_cols_array_2d(&[
-            [pixels_x / 2.0, 0.0, 0.0, pixels_x / 2.0],
-            [0.0, pixels_y / 2.0, 0.0, pixels_y / 2.0],
-            [0.0, 0.0, 1.0, 0.0],
-            [0.0, 0.0, 0.0, 1.0],
-        ])
-        .transpose();
+        let mut affines = PreparedAffines::default();
+        if let Some(target) = render_text.texture {
+            if let Ok(texture) = textures.get(target) {
+                affines.insert(target, texture.affine(Mat4::IDENTITY));
+            }
+            commands.entity(entity).insert(affines);
+            continue;
+        }
+        for (view_entity, camera, view, pixel_scale, view_layers) in views.iter() {
+            if !view_layers
+                .copied()
+                .unwrap_or_default()
+                .intersects(&render_text.render_layers)
+            {
+                continue;
+            }
+            let Some(size_pixels) = camera.physical_viewport_size else {
+                continue;
+            };
+            let (pixels_x, pixels_y) = (size_pixels.x as f32, size_pixels.y as f32);
+            let ndc_to_pixels_matrix = Mat4::from_cols_array_2d(&[
+                [pixels_x / 2.0, 0.0, 0.0, pixels_x / 2.0],
+                [0.0, pixels_y / 2.0, 0.0, pixels_y / 2.0],
+                [0.0, 0.0, 1.0, 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ])
+            .transpose();
+
+            let world_transform = render_text.transform;
+
+            let mut model_matrix = world_transform.compute_matrix();
+            model_matrix.w_axis.y *= -1.0;
+
+            let (projection_mat, view_mat) = {
+                let mut view_mat = view.transform.compute_matrix();
+                view_mat.w_axis.y *= -1.0;
+
+                (view.projection, view_mat)
+            };
+
+            let view_proj_matrix = projection_mat * view_mat.inverse();
+            let vello_matrix = ndc_to_pixels_matrix * view_proj_matrix;
+
+            let raw_transform = match render_text.render_mode {
+                CoordinateSpace::ScreenSpace => {
+                    world_transform.compute_matrix().mul_scalar(pixel_scale.0)
+                }
+                CoordinateSpace::WorldSpace => vello_matrix * model_matrix,
+            };
 
-        let world_transform = render_text.transform;
+            let transform = vello_affine_coeffs(raw_transform).map(f64::from);
+            affines.insert(view_entity, Affine::new(transform));
+        }
+
+        commands.entity(entity).insert(affines);
+    }
+}
 
-        let mut model_matrix = world_transform.compute_matrix();
-        model_matrix.w_axis.y *= -1.0;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::render::camera::CameraProjection;
+    use vello::kurbo::Point;
 
-        let (projection_mat, view_mat) = {
-            let mut view_mat = view.transform.compute_matrix();
-            view_mat.w_axis.y *= -1.0;
+    const VIEWPORT: UVec2 = UVec2::new(64, 64);
 
-            (view.projection, view_mat)
+    /// A 2D camera at `translation`, like `Camera2dBundle::default()`, over
+    /// a 64x64 viewport.
+    fn view(translation: Vec2) -> ExtractedView {
+        let projection = OrthographicProjection {
+            area: Rect::new(-32.0, -32.0, 32.0, 32.0),
+            ..default()
         };
+        ExtractedView {
+            projection: projection.get_projection_matrix(),
+            transform: GlobalTransform::from_translation(translation.extend(999.9)),
+            view_projection: None,
+            hdr: false,
+            viewport: UVec4::new(0, 0, VIEWPORT.x, VIEWPORT.y),
+            color_grading: default(),
+        }
+    }
 
-        let view_proj_matrix = projection_mat * view_mat.inverse();
-        let vello_matrix = ndc_to_pixels_matrix * view_proj_matrix;
+    #[track_caller]
+    fn assert_maps(affine: Affine, from: (f64, f64), to: (f64, f64)) {
+        let mapped = affine * Point::from(from);
+        assert!(
+            (mapped - Point::from(to)).hypot() < 1e-3,
+            "{from:?} mapped to {mapped:?} instead of {to:?}"
+        );
+    }
 
-        let raw_transform = match render_text.render_mode {
-            CoordinateSpace::ScreenSpace => {
-                world_transform.compute_matrix().mul_scalar(pixel_scale.0)
-            }
-            CoordinateSpace::WorldSpace => vello_matrix * model_matrix,
-        };
+    #[test]
+    fn world_space_content_is_y_down_from_the_view_center() {
+        let affine = scene_affine(
+            GlobalTransform::IDENTITY,
+            CoordinateSpace::WorldSpace,
+            &view(Vec2::ZERO),
+            1.0,
+            VIEWPORT,
+        );
+        assert_maps(affine, (0.0, 0.0), (32.0, 32.0));
+        assert_maps(affine, (10.0, 5.0), (42.0, 37.0));
+    }
 
-        let transform: [f32; 16] = raw_transform.to_cols_array();
+    #[test]
+    fn world_space_transforms_are_y_up() {
+        let affine = scene_affine(
+            GlobalTransform::from_xyz(10.0, 10.0, 0.0),
+            CoordinateSpace::WorldSpace,
+            &view(Vec2::ZERO),
+            1.0,
+            VIEWPORT,
+        );
+        assert_maps(affine, (0.0, 0.0), (42.0, 22.0));
+
+        // Counterclockwise, like the rest of the world
+        let affine = scene_affine(
+            GlobalTransform::from_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_2)),
+            CoordinateSpace::WorldSpace,
+            &view(Vec2::ZERO),
+            1.0,
+            VIEWPORT,
+        );
+        assert_maps(affine, (10.0, 0.0), (32.0, 22.0));
+    }
 
-        // | a c e |
-        // | b d f |
-        // | 0 0 1 |
-        let transform: [f64; 6] = [
-            transform[0] as f64,  // a
-            -transform[1] as f64, // b
-            -transform[4] as f64, // c
-            transform[5] as f64,  // d
-            transform[12] as f64, // e
-            transform[13] as f64, // f
-        ];
+    #[test]
+    fn world_space_follows_the_view() {
+        let affine = scene_affine(
+            GlobalTransform::IDENTITY,
+            CoordinateSpace::WorldSpace,
+            &view(Vec2::new(10.0, -10.0)),
+            1.0,
+            VIEWPORT,
+        );
+        assert_maps(affine, (0.0, 0.0), (22.0, 22.0));
+    }
 
-        commands
-            .entity(entity)
-            .insert(PreparedAffine(Affine::new(transform)));
+    #[test]
+    fn screen_space_is_scaled_to_physical_pixels() {
+        let affine = scene_affine(
+            GlobalTransform::from_xyz(8.0, 0.0, 0.0),
+            CoordinateSpace::ScreenSpace,
+            &view(Vec2::new(100.0, 100.0)),
+            2.0,
+            VIEWPORT,
+        );
+        assert_maps(affine, (0.0, 0.0), (16.0, 0.0));
+        assert_maps(affine, (1.0, 1.0), (18.0, 2.0));
+    }
+
+    #[test]
+    fn screen_space_assets_are_centered_on_their_transform() {
+        let asset = VelloAsset {
+            file: crate::VectorFile::Scene(default()),
+            local_transform_center: Transform::from_xyz(5.0, -10.0, 0.0),
+            width: 10.0,
+            height: 20.0,
+            alpha: 1.0,
+        };
+        let matrix =
+            screen_space_asset_matrix(&asset, GlobalTransform::from_xyz(100.0, 50.0, 0.0), None);
+        let affine = Affine::new(vello_affine_coeffs(matrix).map(f64::from));
+        assert_maps(affine, (0.0, 0.0), (95.0, 40.0));
+        assert_maps(affine, (10.0, 20.0), (105.0, 60.0));
     }
 }
@@ -0,0 +1,140 @@
+//! Copying rendered images back to the CPU.
+
+use bevy::prelude::*;
+use bevy::render::extract_component::ExtractComponent;
+use bevy::render::render_asset::{RenderAssetUsages, RenderAssets};
+use bevy::render::render_resource::{
+    BufferDescriptor, BufferUsages, CommandEncoderDescriptor, Extent3d, ImageCopyBuffer,
+    ImageDataLayout, Maintain, MapMode, TextureDimension,
+};
+use bevy::render::renderer::{RenderDevice, RenderQueue};
+use std::sync::{Arc, Mutex};
+
+/// Reads the contents of an [`Image`] back from the GPU after every frame.
+///
+/// The image must be created with [`TextureUsages::COPY_SRC`](bevy::render::render_resource::TextureUsages::COPY_SRC).
+/// Readback blocks the render world until the copy completes, so it is meant
+/// for headless rendering and tests rather than for every frame of a game.
+#[derive(Component, Clone)]
+pub struct ImageReadback {
+    /// The image to read back.
+    pub image: Handle<Image>,
+    latest: Arc<Mutex<Option<Image>>>,
+}
+
+impl ImageReadback {
+    pub fn new(image: Handle<Image>) -> Self {
+        Self {
+            image,
+            latest: default(),
+        }
+    }
+
+    /// The most recently read back frame, if one has been rendered yet.
+    pub fn latest(&self) -> Option<Image> {
+        self.latest.lock().ok()?.clone()
+    }
+
+    /// Take the most recently read back frame, so the next call only returns
+    /// a frame rendered after this one.
+    pub fn take(&self) -> Option<Image> {
+        self.latest.lock().ok()?.take()
+    }
+}
+
+impl ExtractComponent for ImageReadback {
+    type QueryData = &'static ImageReadback;
+
+    type QueryFilter = ();
+
+    type Out = Self;
+
+    fn extract_component(
+        readback: bevy::ecs::query::QueryItem<'_, Self::QueryData>,
+    ) -> Option<Self> {
+        Some(readback.clone())
+    }
+}
+
+pub fn readback_images(
+    readbacks: Query<&ImageReadback>,
+    gpu_images: Res<RenderAssets<Image>>,
+    device: Res<RenderDevice>,
+    queue: Res<RenderQueue>,
+) {
+    for readback in readbacks.iter() {
+        let Some(gpu_image) = gpu_images.get(&readback.image) else {
+            continue;
+        };
+        let Some(block_size) = gpu_image.texture_format.block_copy_size(None) else {
+            warn!(
+                "Cannot read back {:?}, its format {:?} has no fixed texel size",
+                readback.image, gpu_image.texture_format
+            );
+            continue;
+        };
+        let size = Extent3d {
+            width: gpu_image.size.x as u32,
+            height: gpu_image.size.y as u32,
+            depth_or_array_layers: 1,
+        };
+        let row_bytes = (size.width * block_size) as usize;
+        let padded_row_bytes = RenderDevice::align_copy_bytes_per_row(row_bytes);
+
+        let buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("vello_readback_buffer"),
+            size: (padded_row_bytes * size.height as usize) as u64,
+            usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("vello_readback"),
+        });
+        encoder.copy_texture_to_buffer(
+            gpu_image.texture.as_image_copy(),
+            ImageCopyBuffer {
+                buffer: &buffer,
+                layout: ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_row_bytes as u32),
+                    rows_per_image: None,
+                },
+            },
+            size,
+        );
+        queue.submit([encoder.finish()]);
+
+        let slice = buffer.slice(..);
+        let (tx, rx) = std::sync::mpsc::channel();
+        slice.map_async(MapMode::Read, move |result| {
+            let _ = tx.send(result);
+        });
+        device.poll(Maintain::Wait);
+        match rx.recv() {
+            Ok(Ok(())) => {}
+            Ok(Err(err)) => {
+                error!("Could not read back {:?}: {err}", readback.image);
+                continue;
+            }
+            Err(_) => continue,
+        }
+
+        // Strip the row padding wgpu requires for buffer copies
+        let mut data = Vec::with_capacity(row_bytes * size.height as usize);
+        for row in slice.get_mapped_range().chunks(padded_row_bytes) {
+            data.extend_from_slice(&row[..row_bytes]);
+        }
+        buffer.unmap();
+
+        let image = Image::new(
+            size,
+            TextureDimension::D2,
+            data,
+            gpu_image.texture_format,
+            RenderAssetUsages::MAIN_WORLD,
+        );
+        if let Ok(mut latest) = readback.latest.lock() {
+            latest.replace(image);
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use super::blend::VelloBlend;
+use super::clip::{InVelloClips, VelloClip};
+use super::extract::SSRenderTarget;
+use super::layer::InVelloLayer;
+use super::opacity::InheritedVelloOpacity;
+use super::shadow::VelloShadow;
+use super::texture::VelloTexture;
+use super::{VelloRenderSettings, ZFunction};
+use crate::shape::{VelloFill, VelloShape, VelloStroke};
+use crate::text::VelloTextAlignment;
+use crate::{CoordinateSpace, VelloAsset, VelloAssetAlignment, VelloFont, VelloScene, VelloText};
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::render::extract_resource::ExtractResource;
+use bevy::render::view::RenderLayers;
+
+/// Whether Vello content must be redrawn this frame, when
+/// [`VelloRenderSettings::render_on_change`] is set.
+///
+/// Changes to Vello content, its transforms and visibility, cameras and
+/// canvases are detected automatically. Call [`VelloRedraw::request`] to
+/// redraw for anything else.
+#[derive(Resource, ExtractResource, Clone, Debug)]
+pub struct VelloRedraw {
+    needed: bool,
+}
+
+impl Default for VelloRedraw {
+    fn default() -> Self {
+        // Always draw the first frame
+        Self { needed: true }
+    }
+}
+
+impl VelloRedraw {
+    /// Redraw every canvas and texture this frame.
+    pub fn request(&mut self) {
+        self.needed = true;
+    }
+
+    /// Whether anything is redrawn this frame.
+    pub fn is_needed(&self) -> bool {
+        self.needed
+    }
+}
+
+/// Vello content, filtered by the components that change how it is drawn.
+type ContentChanged = (
+    Or<(With<Handle<VelloAsset>>, With<VelloScene>, With<VelloText>)>,
+    Or<(
+        Changed<GlobalTransform>,
+        Changed<InheritedVisibility>,
+        Changed<Handle<VelloAsset>>,
+        Changed<VelloAssetAlignment>,
+        Changed<VelloShape>,
+        Changed<VelloFill>,
+        Changed<VelloStroke>,
+        Changed<VelloScene>,
+        Changed<VelloText>,
+        Changed<VelloTextAlignment>,
+        Changed<Handle<VelloFont>>,
+        Changed<CoordinateSpace>,
+        Changed<ZFunction>,
+        Changed<RenderLayers>,
+        Changed<Node>,
+    )>,
+);
+
+/// Vello content, filtered by the components that change how it is
+/// composited.
+type CompositingChanged = (
+    Or<(With<Handle<VelloAsset>>, With<VelloScene>, With<VelloText>)>,
+    Or<(
+        Changed<InVelloLayer>,
+        Changed<InVelloClips>,
+        Changed<VelloBlend>,
+        Changed<InheritedVelloOpacity>,
+        Changed<VelloShadow>,
+        Changed<VelloTexture>,
+    )>,
+);
+
+#[derive(SystemParam)]
+pub struct ChangedContent<'w, 's> {
+    content: Query<'w, 's, (), ContentChanged>,
+    compositing: Query<'w, 's, (), CompositingChanged>,
+    clips: Query<
+        'w,
+        's,
+        (),
+        (
+            With<VelloClip>,
+            Or<(Changed<VelloClip>, Changed<GlobalTransform>)>,
+        ),
+    >,
+    cameras: Query<
+        'w,
+        's,
+        (),
+        (
+            With<Camera2d>,
+            Or<(
+                Changed<Camera>,
+                Changed<GlobalTransform>,
+                Changed<OrthographicProjection>,
+            )>,
+        ),
+    >,
+    canvases: Query<'w, 's, (), Changed<SSRenderTarget>>,
+}
+
+impl ChangedContent<'_, '_> {
+    fn any(&self) -> bool {
+        !self.content.is_empty()
+            || !self.compositing.is_empty()
+            || !self.clips.is_empty()
+            || !self.cameras.is_empty()
+            || !self.canvases.is_empty()
+    }
+}
+
+#[derive(SystemParam)]
+pub struct RemovedContent<'w, 's> {
+    assets: RemovedComponents<'w, 's, Handle<VelloAsset>>,
+    scenes: RemovedComponents<'w, 's, VelloScene>,
+    texts: RemovedComponents<'w, 's, VelloText>,
+    clips: RemovedComponents<'w, 's, VelloClip>,
+    in_clips: RemovedComponents<'w, 's, InVelloClips>,
+    layers: RemovedComponents<'w, 's, InVelloLayer>,
+    blends: RemovedComponents<'w, 's, VelloBlend>,
+    opacities: RemovedComponents<'w, 's, InheritedVelloOpacity>,
+    shadows: RemovedComponents<'w, 's, VelloShadow>,
+    textures: RemovedComponents<'w, 's, VelloTexture>,
+    render_layers: RemovedComponents<'w, 's, RenderLayers>,
+}
+
+impl RemovedContent<'_, '_> {
+    /// Whether anything was removed, consuming the removals.
+    fn any(&mut self) -> bool {
+        // Read every reader, so none of them report stale removals later
+        [
+            self.assets.read().count(),
+            self.scenes.read().count(),
+            self.texts.read().count(),
+            self.clips.read().count(),
+            self.in_clips.read().count(),
+            self.layers.read().count(),
+            self.blends.read().count(),
+            self.opacities.read().count(),
+            self.shadows.read().count(),
+            self.textures.read().count(),
+            self.render_layers.read().count(),
+        ]
+        .into_iter()
+        .any(|removed| removed > 0)
+    }
+}
+
+/// Request a redraw when anything Vello draws changed this frame.
+pub fn detect_vello_changes(
+    mut redraw: ResMut<VelloRedraw>,
+    settings: Res<VelloRenderSettings>,
+    changed: ChangedContent,
+    mut removed: RemovedContent,
+    mut asset_events: EventReader<AssetEvent<VelloAsset>>,
+    mut font_events: EventReader<AssetEvent<VelloFont>>,
+    #[cfg(feature = "lottie")] animations: Query<
+        (),
+        Or<(Changed<crate::Playhead>, Changed<crate::Theme>)>,
+    >,
+) {
+    let assets_changed = asset_events.read().count() > 0;
+    let fonts_changed = font_events.read().count() > 0;
+    if removed.any() || assets_changed || fonts_changed || settings.is_changed() || changed.any() {
+        redraw.request();
+    }
+    // Playing animations redraw every frame
+    #[cfg(feature = "lottie")]
+    if !animations.is_empty() {
+        redraw.request();
+    }
+}
+
+/// Start every frame without a pending redraw.
+pub fn reset_vello_redraw(mut redraw: ResMut<VelloRedraw>) {
+    // Only touch the resource when it changes, so it is only re-extracted then
+    if redraw.needed {
+        redraw.needed = false;
+    }
+}
+
+/// Run condition for systems that draw Vello content.
+pub fn vello_redraw_needed(settings: Res<VelloRenderSettings>, redraw: Res<VelloRedraw>) -> bool {
+    !settings.render_on_change || redraw.needed
+}
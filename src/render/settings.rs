@@ -0,0 +1,130 @@
+use bevy::prelude::*;
+use bevy::render::extract_resource::ExtractResource;
+#[cfg(not(target_arch = "wasm32"))]
+use bevy::render::renderer::{initialize_renderer, RenderInstance};
+#[cfg(not(target_arch = "wasm32"))]
+use bevy::render::settings::{RenderCreation, WgpuSettings};
+use std::num::NonZeroUsize;
+use vello::{AaConfig, AaSupport, RendererOptions};
+
+/// Settings used to configure the Vello renderer.
+///
+/// Changing the antialiasing method, the number of initialization threads or
+/// `use_cpu` at runtime rebuilds the renderer on the next frame.
+#[derive(Resource, ExtractResource, Clone, PartialEq)]
+pub struct VelloRenderSettings {
+    /// The antialiasing method used to rasterize all Vello content. MSAA
+    /// reduces shimmering on thin strokes, at a higher GPU cost.
+    pub antialiasing: AaConfig,
+    /// The color every canvas is cleared to before content is rendered.
+    pub base_color: Color,
+    /// The number of threads used to compile the renderer's shaders. When
+    /// `None`, all available threads are used.
+    pub num_init_threads: Option<NonZeroUsize>,
+    /// Run Vello's pipeline stages with CPU shaders instead of GPU compute
+    /// shaders. This is used automatically when the GPU renderer can't be
+    /// created, e.g. on machines with only a software adapter.
+    pub use_cpu: bool,
+    /// Only encode and render Vello content on frames where something changed,
+    /// keeping the previous canvases otherwise. See [`VelloRedraw`](super::VelloRedraw)
+    /// to force a redraw.
+    pub render_on_change: bool,
+}
+
+// `AaConfig` doesn't implement `Debug`
+impl std::fmt::Debug for VelloRenderSettings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let antialiasing = match self.antialiasing {
+            AaConfig::Area => "Area",
+            AaConfig::Msaa8 => "Msaa8",
+            AaConfig::Msaa16 => "Msaa16",
+        };
+        f.debug_struct("VelloRenderSettings")
+            .field("antialiasing", &format_args!("{antialiasing}"))
+            .field("base_color", &self.base_color)
+            .field("num_init_threads", &self.num_init_threads)
+            .field("use_cpu", &self.use_cpu)
+            .field("render_on_change", &self.render_on_change)
+            .finish()
+    }
+}
+
+impl Default for VelloRenderSettings {
+    fn default() -> Self {
+        Self {
+            antialiasing: AaConfig::Area,
+            base_color: Color::NONE,
+            num_init_threads: None,
+            use_cpu: false,
+            render_on_change: false,
+        }
+    }
+}
+
+impl VelloRenderSettings {
+    /// Whether a renderer created with these settings must be rebuilt to
+    /// honor `other`.
+    pub(crate) fn requires_rebuild(&self, other: &Self) -> bool {
+        self.antialiasing != other.antialiasing
+            || self.num_init_threads != other.num_init_threads
+            || self.use_cpu != other.use_cpu
+    }
+
+    pub(crate) fn renderer_options(&self) -> RendererOptions {
+        RendererOptions {
+            surface_format: None,
+            use_cpu: self.use_cpu,
+            // Only compile the shaders needed by the selected method
+            antialiasing_support: AaSupport {
+                area: self.antialiasing == AaConfig::Area,
+                msaa8: self.antialiasing == AaConfig::Msaa8,
+                msaa16: self.antialiasing == AaConfig::Msaa16,
+            },
+            num_init_threads: self.num_init_threads,
+        }
+    }
+
+    pub(crate) fn base_color(&self) -> vello::peniko::Color {
+        vello::peniko::Color::rgba(
+            self.base_color.r().into(),
+            self.base_color.g().into(),
+            self.base_color.b().into(),
+            self.base_color.a().into(),
+        )
+    }
+}
+
+/// Create the render device on wgpu's fallback adapter, a software rasterizer
+/// such as lavapipe or WARP, for machines without a GPU. Use it as the
+/// [`RenderPlugin`](bevy::render::RenderPlugin)'s `render_creation`, along with
+/// [`VelloRenderSettings::use_cpu`].
+///
+/// Returns `None` when no fallback adapter is installed.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn fallback_adapter_render_creation(settings: WgpuSettings) -> Option<RenderCreation> {
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+        backends: settings.backends.unwrap_or(wgpu::Backends::all()),
+        dx12_shader_compiler: settings.dx12_shader_compiler.clone(),
+        flags: settings.instance_flags,
+        gles_minor_version: settings.gles3_minor_version,
+    });
+    let request_adapter_options = wgpu::RequestAdapterOptions {
+        power_preference: settings.power_preference,
+        force_fallback_adapter: true,
+        compatible_surface: None,
+    };
+    // `initialize_renderer` panics without an adapter
+    bevy::tasks::block_on(instance.request_adapter(&request_adapter_options))?;
+    let (device, queue, adapter_info, adapter) = bevy::tasks::block_on(initialize_renderer(
+        &instance,
+        &settings,
+        &request_adapter_options,
+    ));
+    Some(RenderCreation::Manual(
+        device,
+        queue,
+        adapter_info,
+        adapter,
+        RenderInstance(std::sync::Arc::new(instance)),
+    ))
+}
@@ -0,0 +1,67 @@
+//! Drop shadows and glows beneath Vello content.
+
+use super::blur::blur_target_image;
+use bevy::prelude::*;
+
+/// The shadow texture of canvases without any [`VelloShadow`]s.
+pub const SHADOWS_PLACEHOLDER_HANDLE: Handle<Image> = Handle::weak_from_u128(8473928172649183720);
+
+/// Draws a blurred silhouette of this entity's Vello content beneath it, for
+/// drop shadows and outer glows.
+///
+/// The offset, blur and spread are in the content's own units, so shadows
+/// scale and rotate with it. Like CSS shadows, a positive `offset.y` moves the
+/// shadow down. On screen, blur and spread are limited to
+/// [`MAX_BLUR_RADIUS`](super::MAX_BLUR_RADIUS) physical pixels.
+///
+/// Shadows are drawn beneath all Vello content on the same canvas. Put content
+/// in a [`VelloLayer`](super::VelloLayer) to have the shadows of the layer
+/// beneath it fall onto it. Content rendered into a
+/// [`VelloTexture`](super::VelloTexture) has no shadows.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct VelloShadow {
+    /// How far the shadow is moved from the content.
+    pub offset: Vec2,
+    /// The radius of the shadow's blur. `0.0` makes a hard shadow.
+    pub blur: f32,
+    /// How far the silhouette grows before it is blurred.
+    pub spread: f32,
+    pub color: Color,
+}
+
+impl Default for VelloShadow {
+    fn default() -> Self {
+        Self {
+            offset: Vec2::new(0.0, 4.0),
+            blur: 8.0,
+            spread: 0.0,
+            color: Color::rgba(0.0, 0.0, 0.0, 0.5),
+        }
+    }
+}
+
+impl VelloShadow {
+    /// A glow of `color` all around the content.
+    pub fn glow(color: Color, blur: f32) -> Self {
+        Self {
+            offset: Vec2::ZERO,
+            blur,
+            spread: 0.0,
+            color,
+        }
+    }
+
+    pub(crate) fn vello_color(&self, opacity: f32) -> vello::peniko::Color {
+        vello::peniko::Color::rgba(
+            self.color.r().into(),
+            self.color.g().into(),
+            self.color.b().into(),
+            (self.color.a() * opacity).into(),
+        )
+    }
+}
+
+/// The image shadows are blurred into, sampled beneath a canvas.
+pub(crate) fn shadow_image(size: UVec2) -> Image {
+    blur_target_image("vello_shadows", size)
+}
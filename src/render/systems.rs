@@ -1,30 +1,52 @@
-use super::extract::{ExtractedRenderAsset, ExtractedRenderText, SSRenderTarget};
-use super::prepare::PreparedAffine;
-use super::VelloRenderer;
+use super::blur::BlurRenderer;
+use super::cache::EncodingCache;
+use super::extract::{
+    ExtractedClipShape, ExtractedRenderAsset, ExtractedRenderText, ExtractedVelloClip,
+    ExtractedVelloTexture, SSRenderTarget,
+};
+use super::painter::VelloPaint;
+use super::post_process::PostProcessingUniform;
+use super::post_process::{glow_image, GLOW_PLACEHOLDER_HANDLE};
+use super::prepare::{PreparedAffines, PreparedClipAffines};
+use super::shadow::{shadow_image, SHADOWS_PLACEHOLDER_HANDLE};
+use super::{
+    InVelloClips, InVelloLayer, InheritedVelloOpacity, SharedVelloRenderer, VelloClip, VelloLayer,
+    VelloOpacity, VelloPostProcessing, VelloRenderSettings, VelloShadow, VelloTexture,
+};
 use crate::render::extract::ExtractedRenderScene;
 use crate::render::prepare::PreparedZIndex;
-use crate::{CoordinateSpace, VelloCanvasMaterial, VelloFont};
+use crate::text::VelloTextAlignment;
+use crate::{
+    CoordinateSpace, VelloAsset, VelloAssetAlignment, VelloCanvasMaterial, VelloFont, VelloScene,
+    VelloText,
+};
 use bevy::prelude::*;
 use bevy::render::mesh::Indices;
+use bevy::render::primitives::Aabb;
 use bevy::render::render_asset::{RenderAssetUsages, RenderAssets};
 use bevy::render::render_resource::{
     Extent3d, PrimitiveTopology, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+    TextureViewDescriptor,
 };
 use bevy::render::renderer::{RenderDevice, RenderQueue};
-use bevy::render::view::NoFrustumCulling;
+use bevy::render::view::{NoFrustumCulling, RenderLayers, VisibleEntities};
 use bevy::sprite::{MaterialMesh2dBundle, Mesh2dHandle};
-use bevy::window::{WindowResized, WindowResolution};
-use vello::kurbo::Affine;
-use vello::{AaSupport, RenderParams, Renderer, RendererOptions, Scene};
+use bevy::utils::HashSet;
+use std::collections::BTreeMap;
+use vello::kurbo::{self, Affine};
+use vello::peniko::{BlendMode, Compose, Fill, Mix};
+use vello::{RenderParams, Scene};
 
-pub fn setup_image(images: &mut Assets<Image>, window: &WindowResolution) -> Handle<Image> {
+pub fn setup_image(images: &mut Assets<Image>, size: UVec2) -> Handle<Image> {
     let size = Extent3d {
-        width: window.physical_width(),
-        height: window.physical_height(),
+        width: size.x,
+        height: size.y,
         ..default()
     };
 
-    // This is the texture that will be rendered to.
+    // This is the texture that will be rendered to. Vello only renders to
+    // 8-bit storage textures, HDR cameras sample a float copy of it from
+    // `setup_float_image`. Post-processing blurs render back into it.
     let mut image = Image {
         texture_descriptor: TextureDescriptor {
             label: None,
@@ -35,7 +57,8 @@ pub fn setup_image(images: &mut Assets<Image>, window: &WindowResolution) -> Han
             sample_count: 1,
             usage: TextureUsages::TEXTURE_BINDING
                 | TextureUsages::COPY_DST
-                | TextureUsages::STORAGE_BINDING,
+                | TextureUsages::STORAGE_BINDING
+                | TextureUsages::RENDER_ATTACHMENT,
             view_formats: &[],
         },
         ..default()
@@ -46,63 +69,123 @@ pub fn setup_image(images: &mut Assets<Image>, window: &WindowResolution) -> Han
     images.add(image)
 }
 
+/// The `Rgba16Float` texture a canvas is converted into for an HDR camera.
+pub fn setup_float_image(images: &mut Assets<Image>, size: UVec2) -> Handle<Image> {
+    let size = Extent3d {
+        width: size.x,
+        height: size.y,
+        ..default()
+    };
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: None,
+            size,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Rgba16Float,
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..default()
+    };
+    image.resize(size);
+    images.add(image)
+}
+
 /// Transforms all the vectors extracted from the game world and places them in
-/// a scene, and renders the scene to a texture with WGPU
+/// a scene, and renders the scene to each camera's canvas texture and each
+/// [`VelloTexture`](super::VelloTexture) with WGPU
 #[allow(clippy::complexity)]
 pub fn render_scene(
-    ss_render_target: Query<&SSRenderTarget>,
-    query_render_vectors: Query<(&PreparedAffine, &PreparedZIndex, &ExtractedRenderAsset)>,
-    query_render_scenes: Query<(&PreparedAffine, &ExtractedRenderScene)>,
-    query_render_texts: Query<(&PreparedAffine, &ExtractedRenderText)>,
-    mut font_render_assets: ResMut<RenderAssets<VelloFont>>,
+    ss_render_targets: Query<&SSRenderTarget>,
+    textures: Query<(Entity, &ExtractedVelloTexture)>,
+    query_render_vectors: Query<(&PreparedAffines, &PreparedZIndex, &ExtractedRenderAsset)>,
+    query_render_scenes: Query<(&PreparedAffines, &ExtractedRenderScene)>,
+    query_render_texts: Query<(&PreparedAffines, &ExtractedRenderText)>,
+    query_clips: Query<(&ExtractedVelloClip, &PreparedClipAffines)>,
+    vello_assets: Res<RenderAssets<VelloAsset>>,
     gpu_images: Res<RenderAssets<Image>>,
     device: Res<RenderDevice>,
     queue: Res<RenderQueue>,
-    mut vello_renderer: Local<Option<VelloRenderer>>,
+    settings: Res<VelloRenderSettings>,
+    mut vello_renderer: ResMut<SharedVelloRenderer>,
+    mut blur_renderer: ResMut<BlurRenderer>,
+    encoding_cache: Res<EncodingCache>,
     #[cfg(feature = "lottie")] mut velato_renderer: ResMut<super::VelatoRenderer>,
 ) {
-    let renderer = vello_renderer.get_or_insert_with(|| {
-        VelloRenderer(
-            Renderer::new(
-                device.wgpu_device(),
-                RendererOptions {
-                    surface_format: None,
-                    use_cpu: false,
-                    antialiasing_support: AaSupport::area_only(),
-                    num_init_threads: None,
-                },
-            )
-            // TODO: Attempt CPU fallback. Support changing antialias settings.
-            .expect("No GPU Device"),
-        )
-    });
+    let Some(renderer) = vello_renderer.get_or_create(&device, &settings) else {
+        return;
+    };
 
-    if let Ok(SSRenderTarget(render_target_image)) = ss_render_target.get_single() {
-        let gpu_image = gpu_images.get(render_target_image).unwrap();
+    // Canvases are keyed by their camera and only draw their own layer,
+    // textures are keyed by their own entity
+    let targets = ss_render_targets
+        .iter()
+        .map(|target| {
+            let shadows = (target.shadows != SHADOWS_PLACEHOLDER_HANDLE).then_some(&target.shadows);
+            (
+                &target.image,
+                target.camera,
+                Some(target.layer),
+                shadows,
+                Some(target),
+            )
+        })
+        .chain(
+            textures
+                .iter()
+                .map(|(entity, texture)| (&texture.image, entity, None, None, None)),
+        );
+    let mut blur_targets = Vec::new();
+    for (render_target_image, camera, layer, shadows, canvas) in targets {
+        let Some(gpu_image) = gpu_images.get(render_target_image) else {
+            continue;
+        };
+        // Textures are sampled through an sRGB view, which Vello can't write to
+        let storage_view = layer.is_none().then(|| {
+            gpu_image.texture.create_view(&TextureViewDescriptor {
+                format: Some(TextureFormat::Rgba8Unorm),
+                ..default()
+            })
+        });
+        let target_view = storage_view.as_ref().unwrap_or(&gpu_image.texture_view);
 
-        enum RenderItem<'a> {
-            Asset(&'a ExtractedRenderAsset),
-            Scene(&'a ExtractedRenderScene),
-            Text(&'a ExtractedRenderText),
-        }
         let mut render_queue: Vec<(f32, CoordinateSpace, (Affine, RenderItem))> =
             query_render_vectors
                 .iter()
-                .map(|(&a, &b, c)| (*b, c.render_mode, (*a, RenderItem::Asset(c))))
+                .filter_map(|(a, &b, c)| {
+                    if layer.is_some_and(|layer| c.layer != layer) {
+                        return None;
+                    }
+                    let affine = a.get(&camera)?;
+                    let asset = vello_assets.get(&c.asset)?;
+                    Some((*b, c.render_mode, (*affine, RenderItem::Asset(c, asset))))
+                })
                 .collect();
-        render_queue.extend(query_render_scenes.iter().map(|(&a, b)| {
-            (
+        render_queue.extend(query_render_scenes.iter().filter_map(|(a, b)| {
+            if layer.is_some_and(|layer| b.layer != layer) {
+                return None;
+            }
+            let affine = a.get(&camera)?;
+            Some((
                 b.transform.translation().z,
                 b.render_mode,
-                (*a, RenderItem::Scene(b)),
-            )
+                (*affine, RenderItem::Scene(b)),
+            ))
         }));
-        render_queue.extend(query_render_texts.iter().map(|(&a, b)| {
-            (
+        render_queue.extend(query_render_texts.iter().filter_map(|(a, b)| {
+            if layer.is_some_and(|layer| b.layer != layer) {
+                return None;
+            }
+            let affine = a.get(&camera)?;
+            Some((
                 b.transform.translation().z,
                 b.render_mode,
-                (*a, RenderItem::Text(b)),
-            )
+                (*affine, RenderItem::Text(b)),
+            ))
         }));
 
         // Sort by render mode with screen space on top, then by z-index
@@ -116,58 +199,114 @@ pub fn render_scene(
             },
         );
 
+        let target_rect =
+            kurbo::Rect::new(0.0, 0.0, gpu_image.size.x as f64, gpu_image.size.y as f64);
+
+        // Draw the silhouettes of each blur radius and spread, in whole
+        // pixels, together, then blur them onto the shadow texture
+        let shadows = shadows.and_then(|shadows| Some((shadows.id(), gpu_images.get(shadows)?)));
+        if let Some((shadows, gpu_shadows)) = shadows {
+            blur_targets.push(shadows);
+            let mut silhouettes: BTreeMap<(u32, u32), Scene> = BTreeMap::new();
+            for (_, _, (affine, render_item)) in render_queue.iter() {
+                let Some(shadow) = render_item.shadow() else {
+                    continue;
+                };
+                let scale = affine.determinant().abs().sqrt() as f32;
+                let blur = (shadow.blur * scale).round().max(0.0) as u32;
+                let spread = (shadow.spread * scale).round().max(0.0) as u32;
+                encode_silhouette(
+                    silhouettes.entry((blur, spread)).or_default(),
+                    render_item,
+                    *affine,
+                    &shadow,
+                    &item_clips(render_item, &query_clips, camera),
+                    target_rect,
+                    &encoding_cache,
+                    &vello_assets,
+                    #[cfg(feature = "lottie")]
+                    &mut velato_renderer,
+                );
+            }
+            if silhouettes.is_empty() {
+                blur_renderer.clear(&device, &queue, &gpu_shadows.texture_view);
+            }
+            let shadow_textures =
+                blur_renderer.textures(&device, shadows, gpu_shadows.size.as_uvec2());
+            for (i, ((blur, spread), scene)) in silhouettes.iter().enumerate() {
+                if let Err(err) = renderer.render_to_texture(
+                    device.wgpu_device(),
+                    &queue,
+                    scene,
+                    &shadow_textures.input,
+                    &RenderParams {
+                        base_color: vello::peniko::Color::TRANSPARENT,
+                        width: gpu_shadows.size.x as u32,
+                        height: gpu_shadows.size.y as u32,
+                        antialiasing_method: settings.antialiasing,
+                    },
+                ) {
+                    error!("Vello failed to render shadows: {err}");
+                    continue;
+                }
+                blur_renderer.blur_shadows(
+                    &device,
+                    &queue,
+                    &shadow_textures,
+                    &gpu_shadows.texture_view,
+                    *blur,
+                    *spread,
+                    i == 0,
+                );
+            }
+        }
+
         // Apply transforms to the respective fragments and add them to the
         // scene to be rendered
         let mut scene_buffer = Scene::new();
-        for (_, _, (affine, render_item)) in render_queue.iter_mut() {
-            match render_item {
-                RenderItem::Asset(ExtractedRenderAsset {
-                    asset,
-                    #[cfg(feature = "lottie")]
-                    alpha,
-                    #[cfg(feature = "lottie")]
-                    theme,
-                    #[cfg(feature = "lottie")]
-                    playhead,
-                    ..
-                }) => match &asset.file {
-                    #[cfg(feature = "svg")]
-                    crate::VectorFile::Svg(scene) => {
-                        // TODO: Apply alpha
-                        scene_buffer.append(scene, Some(*affine));
-                    }
+        for (_, _, (affine, render_item)) in render_queue.iter() {
+            let clips = item_clips(render_item, &query_clips, camera);
+            for (clip, clip_affine) in clips.iter() {
+                push_clip(&mut scene_buffer, clip, *clip_affine, &vello_assets);
+            }
+            // Layers only cover the item, so compose modes other than
+            // source over leave the rest of the canvas alone
+            let (layer_affine, layer_rect) = render_item
+                .bounds()
+                .map_or((Affine::IDENTITY, target_rect), |bounds| (*affine, bounds));
+            let blend = render_item.blend();
+            if let Some(blend) = blend {
+                scene_buffer.push_layer(blend.mode, blend.opacity, layer_affine, &layer_rect);
+            }
+            let opacity = render_item.opacity();
+            if opacity < 1.0 {
+                scene_buffer.push_layer(Mix::Normal, opacity, layer_affine, &layer_rect);
+            }
+
+            encode_item(
+                &mut scene_buffer,
+                render_item,
+                *affine,
+                &encoding_cache,
+                #[cfg(feature = "lottie")]
+                &mut velato_renderer,
+            );
+
+            if opacity < 1.0 {
+                scene_buffer.pop_layer();
+            }
+            if blend.is_some() {
+                scene_buffer.pop_layer();
+            }
+            for (clip, clip_affine) in clips.iter().rev() {
+                pop_clip(
+                    &mut scene_buffer,
+                    clip,
+                    *clip_affine,
+                    &vello_assets,
                     #[cfg(feature = "lottie")]
-                    crate::VectorFile::Lottie(composition) => {
-                        velato_renderer.render(
-                            {
-                                theme
-                                    .as_ref()
-                                    .map(|cs| cs.recolor(composition))
-                                    .as_ref()
-                                    .unwrap_or(composition)
-                            },
-                            *playhead as f64,
-                            *affine,
-                            *alpha as f64,
-                            &mut scene_buffer,
-                        );
-                    }
-                    #[cfg(not(any(feature = "svg", feature = "lottie")))]
-                    _ => unimplemented!(),
-                },
-                RenderItem::Scene(ExtractedRenderScene { scene, .. }) => {
-                    scene_buffer.append(scene, Some(*affine));
-                }
-                RenderItem::Text(ExtractedRenderText {
-                    font,
-                    text,
-                    alignment,
-                    ..
-                }) => {
-                    if let Some(font) = font_render_assets.get_mut(font) {
-                        font.render(&mut scene_buffer, *affine, text, *alignment);
-                    }
-                }
+                    &mut velato_renderer,
+                );
             }
         }
 
@@ -176,13 +315,12 @@ pub fn render_scene(
         let empty_encodings = render_queue
             .iter()
             .filter(|(_, _, (_, item))| match item {
-                RenderItem::Asset(a) => match &a.asset.file {
+                RenderItem::Asset(_, asset) => match &asset.file {
                     #[cfg(feature = "svg")]
                     crate::VectorFile::Svg(scene) => scene.encoding().is_empty(),
+                    crate::VectorFile::Scene(scene) => scene.encoding().is_empty(),
                     #[cfg(feature = "lottie")]
                     crate::VectorFile::Lottie(composition) => composition.layers.is_empty(),
-                    #[cfg(not(any(feature = "svg", feature = "lottie")))]
-                    _ => unimplemented!(),
                 },
                 RenderItem::Scene(s) => s.scene.encoding().is_empty(),
                 RenderItem::Text(t) => t.text.content.is_empty(),
@@ -190,123 +328,986 @@ pub fn render_scene(
             .count()
             == render_queue.len();
 
-        if !render_queue.is_empty() && !empty_encodings {
-            renderer
-                .render_to_texture(
-                    device.wgpu_device(),
-                    &queue,
-                    &scene_buffer,
-                    &gpu_image.texture_view,
-                    &RenderParams {
-                        base_color: vello::peniko::Color::TRANSPARENT,
-                        width: gpu_image.size.x as u32,
-                        height: gpu_image.size.y as u32,
-                        antialiasing_method: vello::AaConfig::Area,
-                    },
-                )
-                .unwrap();
+        // A target with nothing to draw is still rendered, so it's cleared
+        // instead of showing its last frame
+        if empty_encodings {
+            scene_buffer.fill(
+                Fill::NonZero,
+                Affine::IDENTITY,
+                vello::peniko::Color::TRANSPARENT,
+                None,
+                &target_rect,
+            );
+        }
+
+        if let Err(err) = renderer.render_to_texture(
+            device.wgpu_device(),
+            &queue,
+            &scene_buffer,
+            target_view,
+            &RenderParams {
+                base_color: settings.base_color(),
+                width: gpu_image.size.x as u32,
+                height: gpu_image.size.y as u32,
+                antialiasing_method: settings.antialiasing,
+            },
+        ) {
+            error!("Vello failed to render to {render_target_image:?}: {err}");
+            continue;
+        }
+
+        // Blur the canvas over its shadows for its post-processing, then
+        // convert it for HDR cameras
+        let Some(canvas) = canvas else {
+            continue;
+        };
+        let shadows = shadows.map(|(_, gpu_shadows)| &gpu_shadows.texture_view);
+        let glow = canvas.bloom.and_then(|radius| {
+            let gpu_glow = gpu_images.get(&canvas.glow)?;
+            Some((&gpu_glow.texture_view, radius))
+        });
+        if canvas.blur > 0 || glow.is_some() {
+            blur_targets.push(render_target_image.id());
+            let textures = blur_renderer.textures(
+                &device,
+                render_target_image.id(),
+                gpu_image.size.as_uvec2(),
+            );
+            blur_renderer.blur_canvas(
+                &device,
+                &queue,
+                &textures,
+                target_view,
+                shadows,
+                canvas.blur,
+                glow,
+            );
+        }
+        if let Some(gpu_float) = canvas
+            .float
+            .as_ref()
+            .and_then(|float| gpu_images.get(float))
+        {
+            blur_renderer.convert_canvas(
+                &device,
+                &queue,
+                target_view,
+                shadows,
+                canvas.blur > 0,
+                &gpu_float.texture_view,
+            );
+        }
+    }
+    blur_renderer.retain_textures(&blur_targets);
+}
+
+pub(super) enum RenderItem<'a> {
+    /// An asset instance, with its prepared asset.
+    Asset(&'a ExtractedRenderAsset, &'a VelloAsset),
+    Scene(&'a ExtractedRenderScene),
+    Text(&'a ExtractedRenderText),
+}
+
+impl RenderItem<'_> {
+    fn clips(&self) -> &[Entity] {
+        match self {
+            RenderItem::Asset(a, _) => &a.clips,
+            RenderItem::Scene(s) => &s.clips,
+            RenderItem::Text(t) => &t.clips,
+        }
+    }
+
+    /// The bounds of the item in the space it's encoded in, y down, if known.
+    fn bounds(&self) -> Option<kurbo::Rect> {
+        let aabb = match self {
+            RenderItem::Asset(_, asset) => return Some(asset_bounds(asset)),
+            RenderItem::Scene(s) => s.aabb,
+            RenderItem::Text(t) => t.aabb,
+        }?;
+        let (min, max) = (aabb.min(), aabb.max());
+        Some(kurbo::Rect::new(
+            min.x as f64,
+            -max.y as f64,
+            max.x as f64,
+            -min.y as f64,
+        ))
+    }
+
+    fn blend(&self) -> Option<super::VelloBlend> {
+        match self {
+            RenderItem::Asset(a, _) => a.blend,
+            RenderItem::Scene(s) => s.blend,
+            RenderItem::Text(t) => t.blend,
+        }
+    }
+
+    /// The opacity of the layer the item is drawn in. Assets apply their
+    /// opacity through their own alpha instead.
+    fn opacity(&self) -> f32 {
+        match self {
+            RenderItem::Asset(..) => 1.0,
+            RenderItem::Scene(s) => s.opacity,
+            RenderItem::Text(t) => t.opacity,
+        }
+    }
+
+    fn shadow(&self) -> Option<VelloShadow> {
+        match self {
+            RenderItem::Asset(a, _) => a.shadow,
+            RenderItem::Scene(s) => s.shadow,
+            RenderItem::Text(t) => t.shadow,
         }
     }
 }
 
+/// The clips of an item as seen by `view`, outermost first.
+pub(super) fn item_clips<'a>(
+    item: &RenderItem,
+    query_clips: &'a Query<(&ExtractedVelloClip, &PreparedClipAffines)>,
+    view: Entity,
+) -> Vec<(&'a ExtractedVelloClip, Affine)> {
+    item.clips()
+        .iter()
+        .filter_map(|clip| {
+            let (clip, affines) = query_clips.get(*clip).ok()?;
+            Some((clip, *affines.get(&view)?))
+        })
+        .collect()
+}
+
+/// Encode an item into `scene`, without its clips, blend or opacity layer.
+pub(super) fn encode_item(
+    scene: &mut Scene,
+    item: &RenderItem,
+    affine: Affine,
+    encoding_cache: &EncodingCache,
+    #[cfg(feature = "lottie")] velato_renderer: &mut super::VelatoRenderer,
+) {
+    match item {
+        #[cfg(feature = "lottie")]
+        RenderItem::Asset(
+            ExtractedRenderAsset { entity, .. },
+            VelloAsset {
+                file: crate::VectorFile::Lottie(_),
+                ..
+            },
+        ) => {
+            if let Some(encoding) = encoding_cache.lottie(*entity) {
+                scene.append(encoding, Some(affine));
+            }
+        }
+        RenderItem::Asset(
+            ExtractedRenderAsset {
+                opacity,
+                #[cfg(feature = "lottie")]
+                theme,
+                #[cfg(feature = "lottie")]
+                playhead,
+                ..
+            },
+            asset,
+        ) => encode_asset(
+            scene,
+            asset,
+            affine,
+            #[cfg(feature = "lottie")]
+            theme.as_ref(),
+            #[cfg(feature = "lottie")]
+            *playhead,
+            asset.alpha * *opacity,
+            #[cfg(feature = "lottie")]
+            velato_renderer,
+        ),
+        RenderItem::Scene(ExtractedRenderScene { scene: item, .. }) => {
+            scene.append(item, Some(affine));
+        }
+        RenderItem::Text(text) => {
+            if let Some(encoding) = encoding_cache.text(text.entity) {
+                scene.append(encoding, Some(affine));
+            }
+        }
+    }
+}
+
+/// Encode the silhouette of an item in its shadow's color, moved by the
+/// shadow's offset.
+#[allow(clippy::too_many_arguments)]
+fn encode_silhouette(
+    scene: &mut Scene,
+    item: &RenderItem,
+    affine: Affine,
+    shadow: &VelloShadow,
+    clips: &[(&ExtractedVelloClip, Affine)],
+    target_rect: kurbo::Rect,
+    encoding_cache: &EncodingCache,
+    assets: &RenderAssets<VelloAsset>,
+    #[cfg(feature = "lottie")] velato_renderer: &mut super::VelatoRenderer,
+) {
+    for (clip, clip_affine) in clips.iter() {
+        push_clip(scene, clip, *clip_affine, assets);
+    }
+    scene.push_layer(Mix::Normal, 1.0, Affine::IDENTITY, &target_rect);
+    let offset = Affine::translate((shadow.offset.x as f64, shadow.offset.y as f64));
+    encode_item(
+        scene,
+        item,
+        affine * offset,
+        encoding_cache,
+        #[cfg(feature = "lottie")]
+        velato_renderer,
+    );
+    // Keep the coverage of the content, in the shadow's color
+    scene.push_layer(
+        BlendMode::new(Mix::Normal, Compose::SrcIn),
+        1.0,
+        Affine::IDENTITY,
+        &target_rect,
+    );
+    scene.fill(
+        Fill::NonZero,
+        Affine::IDENTITY,
+        shadow.vello_color(item.opacity()),
+        None,
+        &target_rect,
+    );
+    scene.pop_layer();
+    scene.pop_layer();
+    for (clip, clip_affine) in clips.iter().rev() {
+        pop_clip(
+            scene,
+            clip,
+            *clip_affine,
+            assets,
+            #[cfg(feature = "lottie")]
+            velato_renderer,
+        );
+    }
+}
+
+/// Encode a vector asset into `scene`.
+fn encode_asset(
+    scene: &mut Scene,
+    asset: &VelloAsset,
+    affine: Affine,
+    #[cfg(feature = "lottie")] theme: Option<&crate::Theme>,
+    #[cfg(feature = "lottie")] playhead: f64,
+    alpha: f32,
+    #[cfg(feature = "lottie")] velato_renderer: &mut super::VelatoRenderer,
+) {
+    // Without a file format, only runtime encoded scenes exist
+    #[cfg_attr(
+        not(any(feature = "svg", feature = "lottie")),
+        allow(clippy::infallible_destructuring_match)
+    )]
+    let encoding = match &asset.file {
+        #[cfg(feature = "svg")]
+        crate::VectorFile::Svg(svg) => svg,
+        crate::VectorFile::Scene(encoding) => encoding,
+        #[cfg(feature = "lottie")]
+        crate::VectorFile::Lottie(composition) => {
+            velato_renderer.render(
+                {
+                    theme
+                        .map(|cs| cs.recolor(composition))
+                        .as_ref()
+                        .unwrap_or(composition)
+                },
+                playhead,
+                affine,
+                alpha as f64,
+                scene,
+            );
+            return;
+        }
+    };
+    if alpha < 1.0 {
+        scene.push_layer(Mix::Normal, alpha, affine, &asset_bounds(asset));
+        scene.append(encoding, Some(affine));
+        scene.pop_layer();
+    } else {
+        scene.append(encoding, Some(affine));
+    }
+}
+
+/// Where a mask asset is drawn, centered on its clip's origin.
+fn mask_affine(asset: &VelloAsset, clip_affine: Affine) -> Affine {
+    let center = asset.local_transform_center.translation;
+    clip_affine * Affine::translate((-center.x as f64, center.y as f64))
+}
+
+/// The bounds of an asset, in the space of its own encoding.
+fn asset_bounds(asset: &VelloAsset) -> kurbo::Rect {
+    kurbo::Rect::new(0.0, 0.0, asset.width as f64, asset.height as f64)
+}
+
+/// Start clipping the following content. Every call must be matched by a
+/// [`pop_clip`] once the content is encoded.
+pub(super) fn push_clip(
+    scene: &mut Scene,
+    clip: &ExtractedVelloClip,
+    affine: Affine,
+    assets: &RenderAssets<VelloAsset>,
+) {
+    match &clip.shape {
+        ExtractedClipShape::Rect(rect) => scene.push_layer(Mix::Clip, 1.0, affine, rect),
+        ExtractedClipShape::RoundedRect(rect) => scene.push_layer(Mix::Clip, 1.0, affine, rect),
+        ExtractedClipShape::Path(path) => scene.push_layer(Mix::Clip, 1.0, affine, path),
+        // Content is masked when the clip is popped, once it has been drawn
+        ExtractedClipShape::Mask { asset, .. } => {
+            // Content stays unclipped until the mask is prepared
+            if let Some(mask) = assets.get(asset) {
+                scene.push_layer(
+                    Mix::Normal,
+                    1.0,
+                    mask_affine(mask, affine),
+                    &asset_bounds(mask),
+                );
+            }
+        }
+    }
+}
+
+pub(super) fn pop_clip(
+    scene: &mut Scene,
+    clip: &ExtractedVelloClip,
+    affine: Affine,
+    assets: &RenderAssets<VelloAsset>,
+    #[cfg(feature = "lottie")] velato_renderer: &mut super::VelatoRenderer,
+) {
+    if let ExtractedClipShape::Mask {
+        asset,
+        #[cfg(feature = "lottie")]
+        playhead,
+    } = &clip.shape
+    {
+        // No layer was pushed for a mask that isn't prepared
+        let Some(mask) = assets.get(asset) else {
+            return;
+        };
+        // Keep the content only where the mask is drawn
+        let mask_affine = mask_affine(mask, affine);
+        scene.push_layer(
+            BlendMode::new(Mix::Normal, Compose::DestIn),
+            1.0,
+            mask_affine,
+            &asset_bounds(mask),
+        );
+        encode_asset(
+            scene,
+            mask,
+            mask_affine,
+            #[cfg(feature = "lottie")]
+            None,
+            #[cfg(feature = "lottie")]
+            *playhead,
+            mask.alpha,
+            #[cfg(feature = "lottie")]
+            velato_renderer,
+        );
+        scene.pop_layer();
+    }
+    scene.pop_layer();
+}
+
+/// Recreate a camera's canvas texture whenever the camera's viewport changes
+/// size or it's switched to or from HDR.
 pub fn resize_rendertargets(
-    mut window_resize_events: EventReader<WindowResized>,
     mut query: Query<(&mut SSRenderTarget, &Handle<VelloCanvasMaterial>)>,
+    cameras: Query<&Camera, With<Camera2d>>,
     mut images: ResMut<Assets<Image>>,
     mut target_materials: ResMut<Assets<VelloCanvasMaterial>>,
-    windows: Query<&Window>,
 ) {
-    let Ok(window) = windows.get_single() else {
-        return;
-    };
-    if window_resize_events.read().last().is_some() {
-        let size = Extent3d {
-            width: window.resolution.physical_width(),
-            height: window.resolution.physical_height(),
-            ..default()
+    for (mut target, target_mat_handle) in query.iter_mut() {
+        let Ok(camera) = cameras.get(target.camera) else {
+            continue;
         };
-        if size.width == 0 || size.height == 0 {
-            return;
+        let Some(size) = camera.physical_viewport_size() else {
+            continue;
+        };
+        if size.x == 0 || size.y == 0 {
+            continue;
+        }
+        let current_size = images.get(&target.image).map(Image::size);
+        if current_size == Some(size) && target.float.is_some() == camera.hdr {
+            continue;
         }
-        for (mut target, target_mat_handle) in query.iter_mut() {
-            let image = setup_image(&mut images, &window.resolution);
-            if let Some(mat) = target_materials.get_mut(target_mat_handle) {
-                target.0 = image.clone();
-                mat.texture = image;
+        let image = setup_image(&mut images, size);
+        let float = camera.hdr.then(|| setup_float_image(&mut images, size));
+        // Canvases without shadows or bloom keep their placeholders
+        let shadows =
+            (target.shadows != SHADOWS_PLACEHOLDER_HANDLE).then(|| images.add(shadow_image(size)));
+        let glow = (target.glow != GLOW_PLACEHOLDER_HANDLE).then(|| images.add(glow_image(size)));
+        if let Some(mat) = target_materials.get_mut(target_mat_handle) {
+            mat.texture = float.clone().unwrap_or_else(|| image.clone());
+            mat.float = float.is_some();
+            target.image = image;
+            target.float = float;
+            if let Some(shadows) = shadows {
+                target.shadows = shadows.clone();
+                mat.shadows = shadows;
+            }
+            if let Some(glow) = glow {
+                target.glow = glow.clone();
+                mat.glow = glow;
             }
-            debug!(
-                size = format!(
-                    "Resized Vello render image to {:?}",
-                    (size.width, size.height)
-                )
-            );
         }
+        debug!(size = format!("Resized Vello render image to {:?}", (size.x, size.y)));
     }
 }
 
-pub fn setup_ss_rendertarget(
+/// Resize the image of a [`VelloTexture`] when its size changes.
+pub fn resize_vello_textures(
+    query: Query<&VelloTexture, Changed<VelloTexture>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    for texture in query.iter() {
+        let size = texture.size;
+        if size.x == 0 || size.y == 0 {
+            continue;
+        }
+        if images.get(texture.image()).map(Image::size) == Some(size) {
+            continue;
+        }
+        if let Some(image) = images.get_mut(texture.image()) {
+            image.resize(Extent3d {
+                width: size.x,
+                height: size.y,
+                ..default()
+            });
+            debug!(size = format!("Resized Vello texture to {:?}", (size.x, size.y)));
+        }
+    }
+}
+
+/// Spawn a canvas for every `Camera2d` and [`VelloLayer`] pair that does not
+/// have one yet.
+#[allow(clippy::too_many_arguments)]
+pub fn setup_ss_rendertargets(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut images: ResMut<Assets<Image>>,
     mut custom_materials: ResMut<Assets<VelloCanvasMaterial>>,
-    windows: Query<&Window>,
+    cameras: Query<(Entity, &Camera), With<Camera2d>>,
+    layers: Query<Entity, With<VelloLayer>>,
+    render_targets: Query<&SSRenderTarget>,
     mut render_target_mesh_handle: Local<Option<Handle<Mesh>>>,
 ) {
-    let Ok(window) = windows.get_single() else {
-        return;
-    };
+    let existing_canvases: HashSet<(Entity, Option<Entity>)> = render_targets
+        .iter()
+        .map(|target| (target.camera, target.layer))
+        .collect();
 
-    let mesh_handle = render_target_mesh_handle.get_or_insert_with(|| {
-        let mut rendertarget_quad = Mesh::new(
-            PrimitiveTopology::TriangleList,
-            RenderAssetUsages::default(),
-        );
+    let canvases = cameras.iter().flat_map(|camera| {
+        std::iter::once(None)
+            .chain(layers.iter().map(Some))
+            .map(move |layer| (camera, layer))
+    });
+    for ((camera_entity, camera), layer) in canvases {
+        if existing_canvases.contains(&(camera_entity, layer)) {
+            continue;
+        }
+        let Some(size) = camera.physical_viewport_size() else {
+            continue;
+        };
+        if size.x == 0 || size.y == 0 {
+            continue;
+        }
 
-        // Rectangle of the screen
-        let verts = vec![
-            [-1.0, -1.0, 0.0],
-            [1.0, -1.0, 0.0],
-            [1.0, 1.0, 0.0],
-            [-1.0, 1.0, 0.0],
-        ];
-        rendertarget_quad.insert_attribute(Mesh::ATTRIBUTE_POSITION, verts);
+        let mesh_handle = render_target_mesh_handle.get_or_insert_with(|| {
+            let mut rendertarget_quad = Mesh::new(
+                PrimitiveTopology::TriangleList,
+                RenderAssetUsages::default(),
+            );
 
-        let uv_pos = vec![[-1.0, -1.0], [1.0, -1.0], [1.0, 1.0], [1.0, 1.0]];
-        rendertarget_quad.insert_attribute(Mesh::ATTRIBUTE_UV_0, uv_pos);
+            // Rectangle of the screen
+            let verts = vec![
+                [-1.0, -1.0, 0.0],
+                [1.0, -1.0, 0.0],
+                [1.0, 1.0, 0.0],
+                [-1.0, 1.0, 0.0],
+            ];
+            rendertarget_quad.insert_attribute(Mesh::ATTRIBUTE_POSITION, verts);
 
-        let indices = vec![0, 1, 2, 0, 2, 3];
-        rendertarget_quad.insert_indices(Indices::U32(indices));
+            let uv_pos = vec![[-1.0, -1.0], [1.0, -1.0], [1.0, 1.0], [1.0, 1.0]];
+            rendertarget_quad.insert_attribute(Mesh::ATTRIBUTE_UV_0, uv_pos);
 
-        meshes.add(rendertarget_quad)
-    });
-    let texture_image = setup_image(&mut images, &window.resolution);
-    let render_target = SSRenderTarget(texture_image.clone());
-    let mesh = Mesh2dHandle(mesh_handle.clone());
-    let material = custom_materials.add(VelloCanvasMaterial {
-        texture: texture_image,
-    });
+            let indices = vec![0, 1, 2, 0, 2, 3];
+            rendertarget_quad.insert_indices(Indices::U32(indices));
 
-    commands
-        .spawn(MaterialMesh2dBundle {
-            mesh,
-            material,
-            transform: Transform::from_translation(0.001 * Vec3::NEG_Z), // Make sure the vello canvas renders behind Gizmos
-            ..Default::default()
-        })
-        .insert(NoFrustumCulling)
-        .insert(render_target);
+            meshes.add(rendertarget_quad)
+        });
+        let texture_image = setup_image(&mut images, size);
+        let float = camera.hdr.then(|| setup_float_image(&mut images, size));
+        let render_target = SSRenderTarget {
+            image: texture_image.clone(),
+            camera: camera_entity,
+            layer,
+            shadows: SHADOWS_PLACEHOLDER_HANDLE,
+            // Filled in by `update_canvas_post_processing`
+            blur: 0,
+            bloom: None,
+            glow: GLOW_PLACEHOLDER_HANDLE,
+            float: float.clone(),
+        };
+        let mesh = Mesh2dHandle(mesh_handle.clone());
+        let material = custom_materials.add(VelloCanvasMaterial {
+            texture: float.clone().unwrap_or(texture_image),
+            float: float.is_some(),
+            // Filled in by `update_canvas_post_processing`
+            post_processing: default(),
+            // Allocated by `update_canvas_shadows` once there are shadows to draw
+            shadows: SHADOWS_PLACEHOLDER_HANDLE,
+            // Allocated by `update_canvas_post_processing` once there's bloom
+            glow: GLOW_PLACEHOLDER_HANDLE,
+        });
+
+        commands
+            .spawn(MaterialMesh2dBundle {
+                mesh,
+                material,
+                // Layer canvases follow their layer's Z in `sync_layer_canvases`
+                transform: Transform::from_translation(0.001 * Vec3::NEG_Z), // Make sure the vello canvas renders behind Gizmos
+                ..Default::default()
+            })
+            .insert(NoFrustumCulling)
+            // Visible on every layer, `hide_foreign_rendertargets` keeps it to its own camera
+            .insert(RenderLayers::all())
+            .insert(render_target);
+    }
+}
+
+/// Despawn canvases whose camera or layer no longer exists.
+pub fn despawn_orphaned_rendertargets(
+    mut commands: Commands,
+    render_targets: Query<(Entity, &SSRenderTarget)>,
+    cameras: Query<(), With<Camera2d>>,
+    layers: Query<(), With<VelloLayer>>,
+) {
+    for (entity, target) in render_targets.iter() {
+        let orphaned_layer = target.layer.is_some_and(|layer| !layers.contains(layer));
+        if !cameras.contains(target.camera) || orphaned_layer {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// Draw layer canvases at the Z of their [`VelloLayer`], so they sort against
+/// other 2D meshes and sprites.
+pub fn sync_layer_canvases(
+    mut render_targets: Query<(&SSRenderTarget, &mut Transform)>,
+    layers: Query<&GlobalTransform, With<VelloLayer>>,
+) {
+    for (target, mut transform) in render_targets.iter_mut() {
+        let Some(layer) = target.layer else {
+            continue;
+        };
+        let Ok(layer_transform) = layers.get(layer) else {
+            continue;
+        };
+        let z = layer_transform.translation().z;
+        if transform.translation.z != z {
+            transform.translation.z = z;
+        }
+    }
 }
 
-/// Hide the render target canvas if there is nothing to render
-pub fn clear_when_empty(
-    mut query_render_target: Query<&mut Visibility, With<SSRenderTarget>>,
-    render_items: Query<(&mut CoordinateSpace, &ViewVisibility)>,
+/// Keep the post-processing of every canvas in sync with the
+/// [`VelloPostProcessing`] of its layer, its camera, or the resource, and give
+/// canvases with bloom a texture to blur into.
+pub fn update_canvas_post_processing(
+    mut render_targets: Query<(&mut SSRenderTarget, &Handle<VelloCanvasMaterial>)>,
+    overrides: Query<&VelloPostProcessing>,
+    global: Res<VelloPostProcessing>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<VelloCanvasMaterial>>,
+) {
+    for (mut target, material) in render_targets.iter_mut() {
+        let settings = target
+            .layer
+            .and_then(|layer| overrides.get(layer).ok())
+            .or_else(|| overrides.get(target.camera).ok())
+            .unwrap_or(&global);
+        // The canvas is blurred as it's drawn, so these also request a redraw
+        let (blur, bloom) = (settings.blur_radius(), settings.bloom_radius());
+        if target.blur != blur || target.bloom != bloom {
+            target.blur = blur;
+            target.bloom = bloom;
+        }
+
+        let has_glow = target.glow != GLOW_PLACEHOLDER_HANDLE;
+        let glow = match (bloom.is_some(), has_glow) {
+            (true, false) => {
+                let Some(size) = images.get(&target.image).map(Image::size) else {
+                    continue;
+                };
+                Some(images.add(glow_image(size)))
+            }
+            (false, true) => Some(GLOW_PLACEHOLDER_HANDLE),
+            _ => None,
+        };
+
+        let uniform = PostProcessingUniform::from(settings);
+        // Only touch materials that changed, so their bind groups are kept
+        let changed = materials
+            .get(material)
+            .is_some_and(|material| material.post_processing != uniform);
+        if !changed && glow.is_none() {
+            continue;
+        }
+        if let Some(material) = materials.get_mut(material) {
+            material.post_processing = uniform;
+            if let Some(glow) = glow {
+                target.glow = glow.clone();
+                material.glow = glow;
+            }
+        }
+    }
+}
+
+/// Give a canvas a shadow texture while any content drawn onto it has a
+/// [`VelloShadow`], and release it once none does.
+pub fn update_canvas_shadows(
+    mut render_targets: Query<(&mut SSRenderTarget, &Handle<VelloCanvasMaterial>)>,
+    shadows: Query<Option<&InVelloLayer>, With<VelloShadow>>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<VelloCanvasMaterial>>,
 ) {
-    if let Ok(mut visibility) = query_render_target.get_single_mut() {
-        if render_items.is_empty() {
-            *visibility = Visibility::Hidden;
+    let shadowed_layers: HashSet<Option<Entity>> = shadows
+        .iter()
+        .map(|layer| layer.map(|layer| layer.0))
+        .collect();
+    for (mut target, material) in render_targets.iter_mut() {
+        let has_shadows = target.shadows != SHADOWS_PLACEHOLDER_HANDLE;
+        if shadowed_layers.contains(&target.layer) == has_shadows {
+            continue;
+        }
+        let shadows = if has_shadows {
+            SHADOWS_PLACEHOLDER_HANDLE
         } else {
-            *visibility = Visibility::Inherited;
+            let Some(size) = images.get(&target.image).map(Image::size) else {
+                continue;
+            };
+            images.add(shadow_image(size))
+        };
+        if let Some(material) = materials.get_mut(material) {
+            target.shadows = shadows.clone();
+            material.shadows = shadows;
         }
     }
 }
+
+/// Assign Vello content to the nearest [`VelloLayer`] among its ancestors, and
+/// collect the [`VelloClip`]s and multiply the [`VelloOpacity`] of it and its
+/// ancestors, in one pass down each hierarchy. Only runs when a hierarchy or
+/// one of these components changed.
+#[allow(clippy::too_many_arguments)]
+pub fn propagate_vello_hierarchy(
+    mut commands: Commands,
+    changed: Query<
+        (),
+        Or<(
+            Changed<Parent>,
+            Changed<VelloLayer>,
+            Changed<VelloClip>,
+            Changed<VelloOpacity>,
+            Added<Handle<VelloAsset>>,
+            Added<VelloScene>,
+            Added<VelloText>,
+        )>,
+    >,
+    mut removed_parents: RemovedComponents<Parent>,
+    mut removed_layers: RemovedComponents<VelloLayer>,
+    mut removed_clips: RemovedComponents<VelloClip>,
+    mut removed_opacities: RemovedComponents<VelloOpacity>,
+    roots: Query<Entity, Without<Parent>>,
+    nodes: Query<(
+        Has<VelloLayer>,
+        Has<VelloClip>,
+        Option<&VelloOpacity>,
+        Option<&Children>,
+    )>,
+    content: Query<
+        (
+            Option<&InVelloLayer>,
+            Option<&InVelloClips>,
+            Option<&InheritedVelloOpacity>,
+        ),
+        Or<(With<Handle<VelloAsset>>, With<VelloScene>, With<VelloText>)>,
+    >,
+) {
+    // Every reader is drained, so removals aren't seen again next frame
+    let removed = removed_parents.read().count()
+        + removed_layers.read().count()
+        + removed_clips.read().count()
+        + removed_opacities.read().count();
+    if removed == 0 && changed.is_empty() {
+        return;
+    }
+    let mut clips = Vec::new();
+    for root in roots.iter() {
+        propagate_vello_recursive(
+            &mut commands,
+            root,
+            None,
+            &mut clips,
+            None,
+            &nodes,
+            &content,
+        );
+    }
+}
+
+fn propagate_vello_recursive(
+    commands: &mut Commands,
+    entity: Entity,
+    mut layer: Option<Entity>,
+    clips: &mut Vec<Entity>,
+    mut opacity: Option<f32>,
+    nodes: &Query<(
+        Has<VelloLayer>,
+        Has<VelloClip>,
+        Option<&VelloOpacity>,
+        Option<&Children>,
+    )>,
+    content: &Query<
+        (
+            Option<&InVelloLayer>,
+            Option<&InVelloClips>,
+            Option<&InheritedVelloOpacity>,
+        ),
+        Or<(With<Handle<VelloAsset>>, With<VelloScene>, With<VelloText>)>,
+    >,
+) {
+    let Ok((is_layer, is_clip, own_opacity, children)) = nodes.get(entity) else {
+        return;
+    };
+    if is_layer {
+        layer = Some(entity);
+    }
+    if is_clip {
+        clips.push(entity);
+    }
+    if let Some(own_opacity) = own_opacity {
+        opacity = Some(opacity.unwrap_or(1.0) * own_opacity.0);
+    }
+
+    if let Ok((current_layer, current_clips, current_opacity)) = content.get(entity) {
+        match (current_layer, layer.map(InVelloLayer)) {
+            (Some(current), Some(layer)) if *current == layer => {}
+            (_, Some(layer)) => {
+                commands.entity(entity).insert(layer);
+            }
+            (Some(_), None) => {
+                commands.entity(entity).remove::<InVelloLayer>();
+            }
+            (None, None) => {}
+        }
+        match current_clips {
+            Some(current) if current.0 == *clips => {}
+            None if clips.is_empty() => {}
+            Some(_) if clips.is_empty() => {
+                commands.entity(entity).remove::<InVelloClips>();
+            }
+            _ => {
+                commands.entity(entity).insert(InVelloClips(clips.clone()));
+            }
+        }
+        let opacity = opacity.map(|opacity| InheritedVelloOpacity(opacity.clamp(0.0, 1.0)));
+        match (current_opacity, opacity) {
+            (Some(current), Some(opacity)) if *current == opacity => {}
+            (_, Some(opacity)) => {
+                commands.entity(entity).insert(opacity);
+            }
+            (Some(_), None) => {
+                commands.entity(entity).remove::<InheritedVelloOpacity>();
+            }
+            (None, None) => {}
+        }
+    }
+
+    for child in children.into_iter().flatten() {
+        propagate_vello_recursive(commands, *child, layer, clips, opacity, nodes, content);
+    }
+    if is_clip {
+        clips.pop();
+    }
+}
+
+/// Every canvas is a full-screen quad, so only its own camera may draw it,
+/// and only while content visible to that camera is drawn onto it.
+pub fn hide_foreign_rendertargets(
+    mut views: Query<(Entity, &mut VisibleEntities, Option<&RenderLayers>), With<Camera2d>>,
+    render_targets: Query<&SSRenderTarget>,
+    content: Query<
+        (Option<&InVelloLayer>, Has<VelloTexture>),
+        Or<(With<Handle<VelloAsset>>, With<VelloScene>, With<VelloText>)>,
+    >,
+    paint: Res<VelloPaint>,
+) {
+    for (camera, mut visible_entities, view_layers) in views.iter_mut() {
+        // Painted canvases are drawn onto the canvas without a layer
+        let view_layers = view_layers.copied().unwrap_or_default();
+        let painted = paint
+            .canvases
+            .iter()
+            .any(|canvas| canvas.render_layers.intersects(&view_layers));
+        let drawn_layers: HashSet<Option<Entity>> = visible_entities
+            .entities
+            .iter()
+            .filter_map(|entity| content.get(*entity).ok())
+            .filter(|(_, has_texture)| !has_texture)
+            .map(|(layer, _)| layer.map(|layer| layer.0))
+            .chain(painted.then_some(None))
+            .collect();
+        visible_entities.entities.retain(|entity| {
+            render_targets.get(*entity).map_or(true, |target| {
+                target.camera == camera && drawn_layers.contains(&target.layer)
+            })
+        });
+    }
+}
+
+/// Keep an entity's [`Aabb`] in sync, so Bevy can cull it. Screen space
+/// content is never culled, so it has no [`Aabb`].
+fn sync_aabb(
+    commands: &mut Commands,
+    entity: Entity,
+    current: Option<Mut<Aabb>>,
+    coordinate_space: CoordinateSpace,
+    aabb: Option<Aabb>,
+) {
+    match (current, coordinate_space, aabb) {
+        (Some(mut current), CoordinateSpace::WorldSpace, Some(aabb)) => {
+            if *current != aabb {
+                *current = aabb;
+            }
+        }
+        (None, CoordinateSpace::WorldSpace, Some(aabb)) => {
+            commands.entity(entity).insert(aabb);
+        }
+        (Some(_), _, _) => {
+            commands.entity(entity).remove::<Aabb>();
+        }
+        (None, _, _) => {}
+    }
+}
+
+/// Compute the bounds of vector assets from their size and alignment.
+pub fn calculate_asset_bounds(
+    mut commands: Commands,
+    mut query: Query<(
+        Entity,
+        &Handle<VelloAsset>,
+        &VelloAssetAlignment,
+        &CoordinateSpace,
+        Option<&mut Aabb>,
+    )>,
+    assets: Res<Assets<VelloAsset>>,
+) {
+    for (entity, handle, alignment, coordinate_space, current) in query.iter_mut() {
+        let aabb = assets.get(handle).map(|asset| {
+            let bounds = asset.local_bounds(alignment);
+            Aabb::from_min_max(bounds.min.extend(0.0), bounds.max.extend(0.0))
+        });
+        sync_aabb(&mut commands, entity, current, *coordinate_space, aabb);
+    }
+}
+
+/// Compute the bounds of text from its font measurements and alignment.
+pub fn calculate_text_bounds(
+    mut commands: Commands,
+    mut query: Query<(
+        Entity,
+        &Handle<VelloFont>,
+        &VelloText,
+        &VelloTextAlignment,
+        &CoordinateSpace,
+        Option<&mut Aabb>,
+    )>,
+    fonts: Res<Assets<VelloFont>>,
+) {
+    for (entity, handle, text, alignment, coordinate_space, current) in query.iter_mut() {
+        let aabb = fonts.get(handle).map(|font| {
+            let bounds = font.local_bounds(text, *alignment);
+            Aabb::from_min_max(bounds.min.extend(0.0), bounds.max.extend(0.0))
+        });
+        sync_aabb(&mut commands, entity, current, *coordinate_space, aabb);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app() -> App {
+        let mut app = App::new();
+        app.add_systems(Update, propagate_vello_hierarchy);
+        app
+    }
+
+    #[test]
+    fn content_inherits_from_its_ancestors() {
+        let mut app = app();
+        let layer = app
+            .world
+            .spawn((VelloLayer, VelloOpacity(0.5), VelloClip::Rect(default())))
+            .id();
+        let clip = app
+            .world
+            .spawn((VelloClip::Rect(default()), VelloOpacity(0.5)))
+            .set_parent(layer)
+            .id();
+        let scene = app.world.spawn(VelloScene::new()).set_parent(clip).id();
+        app.update();
+
+        let scene = app.world.entity(scene);
+        assert_eq!(scene.get::<InVelloLayer>(), Some(&InVelloLayer(layer)));
+        assert_eq!(
+            scene.get::<InVelloClips>(),
+            Some(&InVelloClips(vec![layer, clip]))
+        );
+        assert_eq!(
+            scene.get::<InheritedVelloOpacity>(),
+            Some(&InheritedVelloOpacity(0.25))
+        );
+    }
+
+    #[test]
+    fn content_follows_hierarchy_changes() {
+        let mut app = app();
+        let layer = app.world.spawn((VelloLayer, VelloOpacity(0.5))).id();
+        let scene = app.world.spawn(VelloScene::new()).set_parent(layer).id();
+        app.update();
+
+        app.world.entity_mut(layer).remove::<VelloOpacity>();
+        app.update();
+        assert!(app.world.get::<InheritedVelloOpacity>(scene).is_none());
+        assert_eq!(
+            app.world.get::<InVelloLayer>(scene),
+            Some(&InVelloLayer(layer))
+        );
+
+        app.world.entity_mut(scene).remove_parent();
+        app.update();
+        assert!(app.world.get::<InVelloLayer>(scene).is_none());
+    }
+
+    #[test]
+    fn unchanged_hierarchies_are_left_alone() {
+        let mut app = app();
+        let layer = app.world.spawn(VelloLayer).id();
+        let scene = app.world.spawn(VelloScene::new()).set_parent(layer).id();
+        app.update();
+
+        // Stale state is only corrected once something changes
+        app.world.entity_mut(scene).remove::<InVelloLayer>();
+        app.update();
+        assert!(app.world.get::<InVelloLayer>(scene).is_none());
+        app.world.entity_mut(layer).insert(VelloOpacity(1.0));
+        app.update();
+        assert_eq!(
+            app.world.get::<InVelloLayer>(scene),
+            Some(&InVelloLayer(layer))
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use bevy::prelude::*;
+use bevy::render::render_resource::{
+    Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+    TextureViewDescriptor,
+};
+
+/// Rasterizes the Vello content of this entity into its own [`Image`] every
+/// frame, instead of drawing it onto camera canvases. Use [`VelloTexture::image`]
+/// as a texture, e.g. the `base_color_texture` of a `StandardMaterial`, to show
+/// vector content on a 3D mesh.
+///
+/// The content is centered on the texture, and its [`GlobalTransform`] places it in
+/// pixels from the texture center, with y up. The texture is resized when
+/// [`VelloTexture::size`] changes.
+#[derive(Component, Clone)]
+pub struct VelloTexture {
+    /// The resolution of the texture, in pixels.
+    pub size: UVec2,
+    image: Handle<Image>,
+}
+
+impl VelloTexture {
+    /// Create a texture of the given resolution.
+    pub fn new(images: &mut Assets<Image>, size: UVec2) -> Self {
+        let extent = Extent3d {
+            width: size.x,
+            height: size.y,
+            ..default()
+        };
+        let mut image = Image {
+            texture_descriptor: TextureDescriptor {
+                label: Some("vello_texture"),
+                size: extent,
+                dimension: TextureDimension::D2,
+                // Vello can only render to storage textures, which can't be sRGB
+                format: TextureFormat::Rgba8Unorm,
+                mip_level_count: 1,
+                sample_count: 1,
+                usage: TextureUsages::TEXTURE_BINDING
+                    | TextureUsages::COPY_DST
+                    | TextureUsages::COPY_SRC
+                    | TextureUsages::STORAGE_BINDING,
+                view_formats: &[TextureFormat::Rgba8UnormSrgb],
+            },
+            // Vello writes sRGB encoded colors, so materials sample them
+            // through an sRGB view to get linear colors back
+            texture_view_descriptor: Some(TextureViewDescriptor {
+                format: Some(TextureFormat::Rgba8UnormSrgb),
+                ..default()
+            }),
+            ..default()
+        };
+        image.resize(extent);
+        Self {
+            size,
+            image: images.add(image),
+        }
+    }
+
+    /// The image the content is rendered into.
+    pub fn image(&self) -> &Handle<Image> {
+        &self.image
+    }
+}
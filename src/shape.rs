@@ -0,0 +1,516 @@
+//! Shape components, drawn like vector assets.
+
+use crate::integrations::VectorLoaderError;
+use crate::{VectorFile, VelloAsset};
+use bevy::ecs::entity::EntityHashSet;
+use bevy::prelude::*;
+use std::sync::Arc;
+use vello::kurbo::{self, Affine, Shape};
+use vello::peniko::{self, Brush, Fill};
+use vello::Scene;
+
+/// The smallest width and height of the asset a shape is encoded into.
+const MIN_EXTENT: f64 = 1.0;
+
+/// How closely curves are flattened to find the bounds of their strokes.
+const TOLERANCE: f64 = 0.1;
+
+/// A shape, drawn with the [`VelloFill`] and [`VelloStroke`] on its entity.
+///
+/// Shapes are encoded into a [`VelloAsset`] whenever the shape, fill or
+/// stroke changes, and its handle is inserted on the entity. They are then
+/// aligned with [`VelloAssetAlignment`](crate::VelloAssetAlignment), sorted
+/// with [`ZFunction`](crate::ZFunction), bounded and drawn exactly like
+/// assets.
+///
+/// Coordinates are y down, like [`VelloScene`](crate::VelloScene) content.
+/// Only the extent of the shape is placed, so a circle around any center
+/// draws in the same place.
+#[derive(Component, Clone, Debug)]
+pub enum VelloShape {
+    Rect(kurbo::Rect),
+    RoundedRect(kurbo::RoundedRect),
+    Circle(kurbo::Circle),
+    Ellipse(kurbo::Ellipse),
+    Line(kurbo::Line),
+    /// A closed polygon through the points.
+    Polygon(Vec<kurbo::Point>),
+    Path(kurbo::BezPath),
+}
+
+impl Default for VelloShape {
+    fn default() -> Self {
+        Self::Path(kurbo::BezPath::new())
+    }
+}
+
+impl VelloShape {
+    pub fn rect(width: f64, height: f64) -> Self {
+        Self::Rect(kurbo::Rect::new(0.0, 0.0, width, height))
+    }
+
+    pub fn rounded_rect(width: f64, height: f64, radius: f64) -> Self {
+        Self::RoundedRect(kurbo::RoundedRect::new(0.0, 0.0, width, height, radius))
+    }
+
+    pub fn circle(radius: f64) -> Self {
+        Self::Circle(kurbo::Circle::new((0.0, 0.0), radius))
+    }
+
+    pub fn ellipse(radius_x: f64, radius_y: f64) -> Self {
+        Self::Ellipse(kurbo::Ellipse::new((0.0, 0.0), (radius_x, radius_y), 0.0))
+    }
+
+    pub fn line(from: impl Into<kurbo::Point>, to: impl Into<kurbo::Point>) -> Self {
+        Self::Line(kurbo::Line::new(from, to))
+    }
+
+    /// Parse SVG path data, the `d` attribute of a `<path>`, like
+    /// `"M0 0 L10 10 Z"`.
+    pub fn from_svg_path(data: &str) -> Result<Self, VectorLoaderError> {
+        Ok(Self::Path(kurbo::BezPath::from_svg(data)?))
+    }
+
+    /// Parse SVG path data, transformed by an SVG `transform` attribute like
+    /// `"translate(10 20) rotate(45)"`.
+    pub fn from_svg_path_with_transform(
+        data: &str,
+        transform: &str,
+    ) -> Result<Self, VectorLoaderError> {
+        let mut path = kurbo::BezPath::from_svg(data)?;
+        path.apply_affine(parse_transform(transform)?);
+        Ok(Self::Path(path))
+    }
+
+    /// The bounds of the shape's geometry, without any stroke.
+    pub fn bounding_box(&self) -> kurbo::Rect {
+        match self {
+            VelloShape::Rect(shape) => shape.bounding_box(),
+            VelloShape::RoundedRect(shape) => shape.bounding_box(),
+            VelloShape::Circle(shape) => shape.bounding_box(),
+            VelloShape::Ellipse(shape) => shape.bounding_box(),
+            VelloShape::Line(shape) => shape.bounding_box(),
+            VelloShape::Polygon(points) => polygon(points).bounding_box(),
+            VelloShape::Path(shape) => shape.bounding_box(),
+        }
+    }
+
+    /// The bounds of the shape's geometry and its stroke outline, with its
+    /// joins, caps and dashes.
+    pub fn stroked_bounding_box(&self, stroke: Option<&VelloStroke>) -> kurbo::Rect {
+        let bounds = self.bounding_box();
+        let Some(stroke) = stroke else {
+            return bounds;
+        };
+        let outline = kurbo::stroke(
+            self.to_path(),
+            &stroke.style,
+            &kurbo::StrokeOpts::default(),
+            TOLERANCE,
+        );
+        if outline.elements().is_empty() {
+            return bounds;
+        }
+        bounds.union(outline.bounding_box())
+    }
+
+    fn to_path(&self) -> kurbo::BezPath {
+        match self {
+            VelloShape::Rect(shape) => shape.to_path(TOLERANCE),
+            VelloShape::RoundedRect(shape) => shape.to_path(TOLERANCE),
+            VelloShape::Circle(shape) => shape.to_path(TOLERANCE),
+            VelloShape::Ellipse(shape) => shape.to_path(TOLERANCE),
+            VelloShape::Line(shape) => shape.to_path(TOLERANCE),
+            VelloShape::Polygon(points) => polygon(points),
+            VelloShape::Path(shape) => shape.clone(),
+        }
+    }
+
+    /// Encode the shape into an asset the size of its bounds, to add to
+    /// `Assets<VelloAsset>` directly instead of spawning the shape.
+    pub fn to_asset(&self, fill: Option<&VelloFill>, stroke: Option<&VelloStroke>) -> VelloAsset {
+        let bounds = self.stroked_bounding_box(stroke);
+        // Lines along an axis and empty paths still get an area, so they can
+        // be scaled to fit a `Node`
+        let bounds = bounds.inflate(
+            ((MIN_EXTENT - bounds.width()) / 2.0).max(0.0),
+            ((MIN_EXTENT - bounds.height()) / 2.0).max(0.0),
+        );
+
+        // Like SVG assets, the encoding starts at the origin
+        let mut scene = Scene::new();
+        let transform = Affine::translate(-bounds.origin().to_vec2());
+        self.encode(&mut scene, transform, fill, stroke);
+
+        let width = bounds.width() as f32;
+        let height = bounds.height() as f32;
+        VelloAsset {
+            file: VectorFile::Scene(Arc::new(scene)),
+            local_transform_center: Transform::from_xyz(width / 2.0, -height / 2.0, 0.0),
+            width,
+            height,
+            alpha: 1.0,
+        }
+    }
+
+    fn encode(
+        &self,
+        scene: &mut Scene,
+        transform: Affine,
+        fill: Option<&VelloFill>,
+        stroke: Option<&VelloStroke>,
+    ) {
+        match self {
+            VelloShape::Rect(shape) => draw(scene, transform, shape, fill, stroke),
+            VelloShape::RoundedRect(shape) => draw(scene, transform, shape, fill, stroke),
+            VelloShape::Circle(shape) => draw(scene, transform, shape, fill, stroke),
+            VelloShape::Ellipse(shape) => draw(scene, transform, shape, fill, stroke),
+            VelloShape::Line(shape) => draw(scene, transform, shape, fill, stroke),
+            VelloShape::Polygon(points) => draw(scene, transform, &polygon(points), fill, stroke),
+            VelloShape::Path(shape) => draw(scene, transform, shape, fill, stroke),
+        }
+    }
+}
+
+/// Fills the [`VelloShape`] on this entity.
+#[derive(Component, Clone, Debug)]
+pub struct VelloFill {
+    pub brush: Brush,
+    pub rule: Fill,
+}
+
+impl Default for VelloFill {
+    fn default() -> Self {
+        Self::new(peniko::Color::WHITE)
+    }
+}
+
+impl VelloFill {
+    pub fn new(brush: impl Into<Brush>) -> Self {
+        Self {
+            brush: brush.into(),
+            rule: Fill::NonZero,
+        }
+    }
+
+    pub fn with_rule(mut self, rule: Fill) -> Self {
+        self.rule = rule;
+        self
+    }
+}
+
+/// Strokes the outline of the [`VelloShape`] on this entity, above its fill.
+#[derive(Component, Clone, Debug)]
+pub struct VelloStroke {
+    /// Width, joins, caps and dashes.
+    pub style: kurbo::Stroke,
+    pub brush: Brush,
+}
+
+impl Default for VelloStroke {
+    fn default() -> Self {
+        Self::new(1.0, peniko::Color::WHITE)
+    }
+}
+
+impl VelloStroke {
+    pub fn new(width: f64, brush: impl Into<Brush>) -> Self {
+        Self {
+            style: kurbo::Stroke::new(width),
+            brush: brush.into(),
+        }
+    }
+
+    pub fn with_join(mut self, join: kurbo::Join) -> Self {
+        self.style = self.style.with_join(join);
+        self
+    }
+
+    pub fn with_caps(mut self, cap: kurbo::Cap) -> Self {
+        self.style = self.style.with_caps(cap);
+        self
+    }
+
+    pub fn with_dashes(mut self, offset: f64, pattern: impl IntoIterator<Item = f64>) -> Self {
+        self.style = self.style.with_dashes(offset, pattern);
+        self
+    }
+}
+
+/// The [`VelloAsset`] a shape is encoded into, reused while the shape exists.
+#[derive(Component)]
+pub(crate) struct ShapeAsset(Handle<VelloAsset>);
+
+fn polygon(points: &[kurbo::Point]) -> kurbo::BezPath {
+    let mut path = kurbo::BezPath::new();
+    let mut points = points.iter();
+    if let Some(first) = points.next() {
+        path.move_to(*first);
+        for point in points {
+            path.line_to(*point);
+        }
+        path.close_path();
+    }
+    path
+}
+
+fn draw(
+    scene: &mut Scene,
+    transform: Affine,
+    shape: &impl Shape,
+    fill: Option<&VelloFill>,
+    stroke: Option<&VelloStroke>,
+) {
+    if let Some(fill) = fill {
+        scene.fill(fill.rule, transform, &fill.brush, None, shape);
+    }
+    if let Some(stroke) = stroke {
+        scene.stroke(&stroke.style, transform, &stroke.brush, None, shape);
+    }
+}
+
+/// Parse an SVG `transform` attribute: a list of `matrix`, `translate`,
+/// `scale`, `rotate`, `skewX` and `skewY` functions, applied right to left.
+fn parse_transform(transform: &str) -> Result<Affine, VectorLoaderError> {
+    let invalid = || VectorLoaderError::Transform(transform.to_string());
+    let is_separator = |c: char| c == ',' || c.is_whitespace();
+
+    let mut affine = Affine::IDENTITY;
+    let mut rest = transform.trim_start_matches(is_separator);
+    while !rest.is_empty() {
+        let (open, close) = rest.find('(').zip(rest.find(')')).ok_or_else(invalid)?;
+        if close < open {
+            return Err(invalid());
+        }
+        let args = rest[open + 1..close]
+            .split(is_separator)
+            .filter(|arg| !arg.is_empty())
+            .map(str::parse::<f64>)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| invalid())?;
+        affine *= match (rest[..open].trim(), args.as_slice()) {
+            ("matrix", &[a, b, c, d, e, f]) => Affine::new([a, b, c, d, e, f]),
+            ("translate", &[x]) => Affine::translate((x, 0.0)),
+            ("translate", &[x, y]) => Affine::translate((x, y)),
+            ("scale", &[scale]) => Affine::scale(scale),
+            ("scale", &[x, y]) => Affine::scale_non_uniform(x, y),
+            ("rotate", &[angle]) => Affine::rotate(angle.to_radians()),
+            ("rotate", &[angle, x, y]) => Affine::rotate_about(angle.to_radians(), (x, y)),
+            ("skewX", &[angle]) => Affine::skew(angle.to_radians().tan(), 0.0),
+            ("skewY", &[angle]) => Affine::skew(0.0, angle.to_radians().tan()),
+            _ => return Err(invalid()),
+        };
+        rest = rest[close + 1..].trim_start_matches(is_separator);
+    }
+    Ok(affine)
+}
+
+/// Re-encode the shapes whose shape, fill or stroke changed.
+pub(crate) fn encode_shapes(
+    mut commands: Commands,
+    shapes: Query<(
+        &VelloShape,
+        Option<&VelloFill>,
+        Option<&VelloStroke>,
+        Option<&ShapeAsset>,
+    )>,
+    changed: Query<
+        Entity,
+        (
+            With<VelloShape>,
+            Or<(
+                Changed<VelloShape>,
+                Changed<VelloFill>,
+                Changed<VelloStroke>,
+            )>,
+        ),
+    >,
+    mut removed_shapes: RemovedComponents<VelloShape>,
+    mut removed_fills: RemovedComponents<VelloFill>,
+    mut removed_strokes: RemovedComponents<VelloStroke>,
+    mut assets: ResMut<Assets<VelloAsset>>,
+) {
+    for entity in removed_shapes.read() {
+        if shapes.contains(entity) {
+            continue;
+        }
+        if let Some(mut entity) = commands.get_entity(entity) {
+            entity.remove::<(ShapeAsset, Handle<VelloAsset>)>();
+        }
+    }
+
+    let stale: EntityHashSet = changed
+        .iter()
+        .chain(removed_fills.read())
+        .chain(removed_strokes.read())
+        .collect();
+    for entity in stale {
+        let Ok((shape, fill, stroke, current)) = shapes.get(entity) else {
+            continue;
+        };
+        let asset = shape.to_asset(fill, stroke);
+        match current.and_then(|current| assets.get_mut(&current.0)) {
+            Some(current) => *current = asset,
+            None => {
+                let handle = assets.add(asset);
+                commands
+                    .entity(entity)
+                    .insert((ShapeAsset(handle.clone()), handle));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_affine(actual: Result<Affine, VectorLoaderError>, expected: Affine) {
+        let actual = actual.unwrap().as_coeffs();
+        let expected = expected.as_coeffs();
+        for (actual, expected) in actual.iter().zip(expected) {
+            assert!(
+                (actual - expected).abs() < 1e-9,
+                "{actual:?} != {expected:?}"
+            );
+        }
+    }
+
+    fn asset<'a>(app: &'a App, handle: &Handle<VelloAsset>) -> &'a VelloAsset {
+        app.world
+            .resource::<Assets<VelloAsset>>()
+            .get(handle)
+            .unwrap()
+    }
+
+    #[test]
+    fn parses_each_transform_function() {
+        assert_affine(
+            parse_transform("matrix(1 2 3 4 5 6)"),
+            Affine::new([1.0, 2.0, 3.0, 4.0, 5.0, 6.0]),
+        );
+        assert_affine(
+            parse_transform("translate(10)"),
+            Affine::translate((10.0, 0.0)),
+        );
+        assert_affine(
+            parse_transform("translate(10, 20)"),
+            Affine::translate((10.0, 20.0)),
+        );
+        assert_affine(parse_transform("scale(2)"), Affine::scale(2.0));
+        assert_affine(
+            parse_transform("scale(2 3)"),
+            Affine::scale_non_uniform(2.0, 3.0),
+        );
+        assert_affine(
+            parse_transform("rotate(90)"),
+            Affine::rotate(std::f64::consts::FRAC_PI_2),
+        );
+        assert_affine(
+            parse_transform("rotate(90 10 20)"),
+            Affine::rotate_about(std::f64::consts::FRAC_PI_2, (10.0, 20.0)),
+        );
+        assert_affine(parse_transform("skewX(45)"), Affine::skew(1.0, 0.0));
+        assert_affine(parse_transform("skewY(45)"), Affine::skew(0.0, 1.0));
+        assert_affine(parse_transform(""), Affine::IDENTITY);
+    }
+
+    #[test]
+    fn parses_transform_lists() {
+        let expected = Affine::translate((10.0, 0.0)) * Affine::rotate(45f64.to_radians());
+        assert_affine(parse_transform("translate(10) rotate(45)"), expected);
+        assert_affine(parse_transform(" translate(10),rotate(45) "), expected);
+        assert_affine(parse_transform("translate(10)rotate(45)"), expected);
+    }
+
+    #[test]
+    fn rejects_malformed_transforms() {
+        for transform in [
+            "translate(10",
+            "translate 10",
+            ")translate(10(",
+            "translate()",
+            "translate(1 2 3)",
+            "rotate(a)",
+            "scale(2) skew(1)",
+            "perspective(1)",
+        ] {
+            assert!(
+                matches!(
+                    parse_transform(transform),
+                    Err(VectorLoaderError::Transform(t)) if t == transform
+                ),
+                "{transform:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn parses_svg_paths() {
+        let shape = VelloShape::from_svg_path("M0 0 L10 10 H20 Z").unwrap();
+        assert_eq!(shape.bounding_box(), kurbo::Rect::new(0.0, 0.0, 20.0, 10.0));
+        let shape =
+            VelloShape::from_svg_path_with_transform("M0 0 L10 10", "translate(5 5) scale(2)")
+                .unwrap();
+        assert_eq!(shape.bounding_box(), kurbo::Rect::new(5.0, 5.0, 25.0, 25.0));
+
+        assert!(VelloShape::from_svg_path("M0 0 X10").is_err());
+        assert!(VelloShape::from_svg_path_with_transform("M0 0 L10 10", "scale(").is_err());
+    }
+
+    #[test]
+    fn strokes_are_bounded_by_their_outline() {
+        let stroke = VelloStroke::new(2.0, peniko::Color::BLACK);
+        let rect = VelloShape::rect(10.0, 10.0).to_asset(None, Some(&stroke));
+        assert_eq!((rect.width, rect.height), (12.0, 12.0));
+
+        // The 30° corner's miter reaches about 3.7 past its point
+        let triangle = VelloShape::Polygon(vec![
+            (0.0, 0.0).into(),
+            (10.0, 0.0).into(),
+            (0.0, 10.0 * 30f64.to_radians().tan()).into(),
+        ]);
+        let asset = triangle.to_asset(None, Some(&stroke.with_join(kurbo::Join::Miter)));
+        assert!(asset.width > 14.0, "{}", asset.width);
+    }
+
+    #[test]
+    fn degenerate_shapes_have_an_extent() {
+        let line = VelloShape::line((0.0, 5.0), (10.0, 5.0)).to_asset(None, None);
+        assert_eq!((line.width, line.height), (10.0, 1.0));
+        let empty = VelloShape::default().to_asset(Some(&VelloFill::default()), None);
+        assert_eq!((empty.width, empty.height), (1.0, 1.0));
+    }
+
+    #[test]
+    fn shapes_are_encoded_only_when_they_change() {
+        let mut app = App::new();
+        app.init_resource::<Assets<VelloAsset>>()
+            .add_systems(Update, encode_shapes);
+        let entity = app
+            .world
+            .spawn((VelloShape::rect(10.0, 20.0), VelloFill::default()))
+            .id();
+        app.update();
+
+        let handle = app.world.get::<Handle<VelloAsset>>(entity).unwrap().clone();
+        assert_eq!(asset(&app, &handle).width, 10.0);
+        // Marks the asset, rebuilding it would reset its alpha
+        let mut assets = app.world.resource_mut::<Assets<VelloAsset>>();
+        assets.get_mut(&handle).unwrap().alpha = 0.5;
+        app.update();
+        assert_eq!(asset(&app, &handle).alpha, 0.5);
+
+        app.world
+            .entity_mut(entity)
+            .insert(VelloStroke::new(2.0, peniko::Color::BLACK));
+        app.update();
+        assert_eq!(app.world.get::<Handle<VelloAsset>>(entity), Some(&handle));
+        assert_eq!(asset(&app, &handle).alpha, 1.0);
+        assert_eq!(asset(&app, &handle).width, 12.0);
+
+        app.world.entity_mut(entity).remove::<VelloShape>();
+        app.update();
+        assert!(app.world.get::<Handle<VelloAsset>>(entity).is_none());
+    }
+}
@@ -0,0 +1,316 @@
+//! Utilities for golden-image tests of Vello content.
+//!
+//! [`SnapshotApp`] builds a windowless [`App`] with the [`VelloPlugin`] and a
+//! single 2D camera rendering into an offscreen image, using Vello's CPU
+//! shaders on wgpu's fallback adapter where one is installed, so snapshots
+//! rasterize the same on every machine. Spawn [`VelloAsset`](crate::VelloAsset)s, [`VelloScene`](crate::VelloScene)s
+//! or [`VelloText`](crate::text::VelloText) into [`SnapshotApp::world_mut`],
+//! call [`SnapshotApp::render`], and compare the frame against a stored PNG
+//! with [`assert_snapshot`].
+//!
+//! Set the `BEVY_VELLO_UPDATE_SNAPSHOTS` environment variable to (re)write the
+//! stored PNGs instead of comparing against them. A missing PNG is written
+//! too, but still fails the comparison, so new snapshots are reviewed before
+//! they're committed.
+
+use crate::render::fallback_adapter_render_creation;
+use crate::{VelloAsset, VelloFont, VelloPlugin, VelloRenderSettings};
+use bevy::app::PluginsState;
+use bevy::prelude::*;
+use bevy::render::camera::RenderTarget;
+use bevy::render::render_resource::{
+    CachedPipelineState, Extent3d, PipelineCache, TextureDescriptor, TextureDimension,
+    TextureFormat, TextureUsages,
+};
+use bevy::render::{RenderApp, RenderPlugin};
+use bevy::utils::thiserror::{self, Error};
+use bevy::window::ExitCondition;
+use bevy::winit::WinitPlugin;
+use std::path::{Path, PathBuf};
+
+/// The environment variable that makes [`assert_snapshot`] write snapshots.
+pub const UPDATE_SNAPSHOTS_ENV: &str = "BEVY_VELLO_UPDATE_SNAPSHOTS";
+
+/// The number of frames [`SnapshotApp::render`] waits for pipelines to compile.
+const MAX_FRAMES: usize = 200;
+
+#[derive(Debug, Error)]
+pub enum SnapshotError {
+    #[error("No frame was rendered after {0} updates")]
+    NoFrame(usize),
+    #[error("Could not read or write snapshot: {0}")]
+    Image(#[from] image::ImageError),
+    #[error("There was no snapshot, the frame was written to it for review")]
+    Missing,
+    #[error("Snapshot is {expected:?} but the frame is {actual:?}")]
+    SizeMismatch { expected: UVec2, actual: UVec2 },
+    #[error("{differing} of {total} pixels differ from the snapshot, see {}", .failed.display())]
+    Mismatch {
+        differing: usize,
+        total: usize,
+        failed: PathBuf,
+    },
+}
+
+/// How far a rendered frame may stray from its snapshot.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tolerance {
+    /// The largest per-channel difference for a pixel to still count as equal.
+    pub channel: u8,
+    /// The fraction of pixels, from 0 to 1, that may differ.
+    pub pixels: f32,
+}
+
+impl Default for Tolerance {
+    fn default() -> Self {
+        // Allow for small rasterization differences between Vello backends.
+        Self {
+            channel: 2,
+            pixels: 0.001,
+        }
+    }
+}
+
+/// A headless app that renders Vello content into an offscreen image.
+pub struct SnapshotApp {
+    pub app: App,
+    camera: Entity,
+    readback: Entity,
+}
+
+impl SnapshotApp {
+    /// Create an app rendering into an image of the given size, cleared to white.
+    pub fn new(size: UVec2) -> Self {
+        let mut plugins = DefaultPlugins
+            .set(WindowPlugin {
+                primary_window: None,
+                exit_condition: ExitCondition::DontExit,
+                ..default()
+            })
+            .disable::<WinitPlugin>()
+            // Keep the render world on this thread, so pipelines can be inspected.
+            .disable::<bevy::render::pipelined_rendering::PipelinedRenderingPlugin>();
+        if let Some(render_creation) = fallback_adapter_render_creation(default()) {
+            plugins = plugins.set(RenderPlugin {
+                render_creation,
+                ..default()
+            });
+        }
+
+        let mut app = App::new();
+        app.add_plugins(plugins)
+            .insert_resource(VelloRenderSettings {
+                use_cpu: true,
+                ..default()
+            })
+            .add_plugins(VelloPlugin);
+
+        // Wait for the renderer to initialize.
+        while app.plugins_state() == PluginsState::Adding {
+            bevy::tasks::tick_global_task_pools_on_main_thread();
+        }
+        app.finish();
+        app.cleanup();
+
+        let image = app
+            .world
+            .resource_mut::<Assets<Image>>()
+            .add(target_image(size));
+        let camera = app
+            .world
+            .spawn(Camera2dBundle {
+                camera: Camera {
+                    target: RenderTarget::Image(image.clone()),
+                    clear_color: ClearColorConfig::Custom(Color::WHITE),
+                    ..default()
+                },
+                ..default()
+            })
+            .id();
+        let readback = app.world.spawn(crate::ImageReadback::new(image)).id();
+
+        Self {
+            app,
+            camera,
+            readback,
+        }
+    }
+
+    /// The camera rendering into the snapshot image.
+    pub fn camera(&self) -> Entity {
+        self.camera
+    }
+
+    /// The main world, to spawn the content under test.
+    pub fn world_mut(&mut self) -> &mut World {
+        &mut self.app.world
+    }
+
+    /// Add a vector asset, ready to be used by a [`VelloAssetBundle`](crate::VelloAssetBundle).
+    pub fn add_asset(&mut self, asset: VelloAsset) -> Handle<VelloAsset> {
+        self.app
+            .world
+            .resource_mut::<Assets<VelloAsset>>()
+            .add(asset)
+    }
+
+    /// Add a font from TTF/OTF bytes, ready to be used by a [`VelloTextBundle`](crate::VelloTextBundle).
+    pub fn add_font(&mut self, font_data: Vec<u8>) -> Handle<VelloFont> {
+        self.app
+            .world
+            .resource_mut::<Assets<VelloFont>>()
+            .add(VelloFont::new(font_data))
+    }
+
+    /// Update the app until every render pipeline has compiled, and return the
+    /// frame rendered after that.
+    pub fn render(&mut self) -> Result<Image, SnapshotError> {
+        self.readback().take();
+        let mut settled = false;
+        for _ in 0..MAX_FRAMES {
+            self.app.update();
+            // The frame is only complete once it was rendered with every
+            // pipeline it queued, so wait one frame after they all compiled.
+            if settled {
+                if let Some(frame) = self.readback().take() {
+                    return Ok(frame);
+                }
+            }
+            settled = self.pipelines_ready();
+        }
+        Err(SnapshotError::NoFrame(MAX_FRAMES))
+    }
+
+    fn readback(&self) -> &crate::ImageReadback {
+        self.app
+            .world
+            .get::<crate::ImageReadback>(self.readback)
+            .expect("snapshot readback was despawned")
+    }
+
+    fn pipelines_ready(&self) -> bool {
+        let Ok(render_app) = self.app.get_sub_app(RenderApp) else {
+            return true;
+        };
+        render_app
+            .world
+            .resource::<PipelineCache>()
+            .pipelines()
+            .all(|pipeline| {
+                !matches!(
+                    pipeline.state,
+                    CachedPipelineState::Queued | CachedPipelineState::Creating(_)
+                )
+            })
+    }
+}
+
+/// Compare a frame against the PNG at `path`.
+///
+/// When [`UPDATE_SNAPSHOTS_ENV`] is set, the frame is written to `path`
+/// instead. When the snapshot is missing, the frame is written to `path` and
+/// [`SnapshotError::Missing`] is returned. On a mismatch, the frame is written
+/// next to the snapshot with a `.failed.png` extension.
+pub fn compare_snapshot(
+    frame: &Image,
+    path: impl AsRef<Path>,
+    tolerance: Tolerance,
+) -> Result<(), SnapshotError> {
+    let path = path.as_ref();
+    let actual = to_rgba8(frame);
+
+    let update = std::env::var_os(UPDATE_SNAPSHOTS_ENV).is_some();
+    if update || !path.exists() {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(image::ImageError::IoError)?;
+        }
+        actual.save(path)?;
+        return if update {
+            Ok(())
+        } else {
+            Err(SnapshotError::Missing)
+        };
+    }
+
+    let expected = image::open(path)?.to_rgba8();
+    if expected.dimensions() != actual.dimensions() {
+        return Err(SnapshotError::SizeMismatch {
+            expected: expected.dimensions().into(),
+            actual: actual.dimensions().into(),
+        });
+    }
+
+    let differing = expected
+        .pixels()
+        .zip(actual.pixels())
+        .filter(|(expected, actual)| {
+            expected
+                .0
+                .iter()
+                .zip(actual.0.iter())
+                .any(|(e, a)| e.abs_diff(*a) > tolerance.channel)
+        })
+        .count();
+    let total = (actual.width() * actual.height()) as usize;
+    if differing as f32 > total as f32 * tolerance.pixels {
+        let failed = path.with_extension("failed.png");
+        actual.save(&failed)?;
+        return Err(SnapshotError::Mismatch {
+            differing,
+            total,
+            failed,
+        });
+    }
+    Ok(())
+}
+
+/// Like [`compare_snapshot`], but panics on a mismatch.
+#[track_caller]
+pub fn assert_snapshot(frame: &Image, path: impl AsRef<Path>, tolerance: Tolerance) {
+    let path = path.as_ref();
+    if let Err(err) = compare_snapshot(frame, path, tolerance) {
+        panic!("Snapshot {} failed: {err}", path.display());
+    }
+}
+
+fn target_image(size: UVec2) -> Image {
+    let size = Extent3d {
+        width: size.x,
+        height: size.y,
+        ..default()
+    };
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: Some("vello_snapshot"),
+            size,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Rgba8UnormSrgb,
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::COPY_SRC
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..default()
+    };
+    image.resize(size);
+    image
+}
+
+fn to_rgba8(frame: &Image) -> image::RgbaImage {
+    let size = frame.size();
+    let mut data = frame.data.clone();
+    // Snapshots are stored as RGBA, whatever the target format.
+    if matches!(
+        frame.texture_descriptor.format,
+        TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb
+    ) {
+        for pixel in data.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+    }
+    image::RgbaImage::from_raw(size.x, size.y, data)
+        .expect("snapshot frames are 8-bit RGBA or BGRA")
+}
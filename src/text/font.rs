@@ -70,26 +70,112 @@ impl VelloFont {
         Vec2::new(width, height)
     }
 
+    /// The bounds of text placed by `alignment`, in the local space of its
+    /// entity.
+    pub(crate) fn local_bounds(&self, text: &VelloText, alignment: VelloTextAlignment) -> Rect {
+        let size = self.sizeof(text);
+        let min_x = match alignment {
+            VelloTextAlignment::TopLeft
+            | VelloTextAlignment::Left
+            | VelloTextAlignment::BottomLeft => 0.0,
+            VelloTextAlignment::Top | VelloTextAlignment::Center | VelloTextAlignment::Bottom => {
+                -size.x / 2.0
+            }
+            VelloTextAlignment::TopRight
+            | VelloTextAlignment::Right
+            | VelloTextAlignment::BottomRight => -size.x,
+        };
+        let min_y = match alignment {
+            VelloTextAlignment::BottomLeft
+            | VelloTextAlignment::Bottom
+            | VelloTextAlignment::BottomRight => 0.0,
+            VelloTextAlignment::Left | VelloTextAlignment::Center | VelloTextAlignment::Right => {
+                -size.y / 2.0
+            }
+            VelloTextAlignment::TopLeft
+            | VelloTextAlignment::Top
+            | VelloTextAlignment::TopRight => -size.y,
+        };
+        // The measured height stops at the baseline, leave room for descenders
+        let descent = text.size / 2.0;
+        Rect::new(min_x, min_y - descent, min_x + size.x, min_y + size.y)
+    }
+
     pub(crate) fn render(
         &self,
         scene: &mut Scene,
-        mut transform: Affine,
+        transform: Affine,
         text: &VelloText,
         alignment: VelloTextAlignment,
     ) {
         let font = FontRef::new(self.font.data.data()).expect("Vello font creation error");
+        let var_loc = font.axes().location(VARIATIONS);
+        let layout = TextLayout::new(&font, &var_loc, text, alignment);
+
+        scene
+            .draw_glyphs(&self.font)
+            .font_size(text.size)
+            .transform(transform * layout.alignment)
+            .normalized_coords(var_loc.coords())
+            .brush(&text.brush.clone().unwrap_or(Brush::Solid(Color::WHITE)))
+            .draw(vello::peniko::Fill::EvenOdd, layout.glyphs.into_iter());
+    }
+
+    /// The box of each glyph, from its advance and the font's ascent and
+    /// descent, in the space [`render`](Self::render) draws the text in.
+    pub(crate) fn glyph_boxes(
+        &self,
+        text: &VelloText,
+        alignment: VelloTextAlignment,
+    ) -> Vec<vello::kurbo::Rect> {
+        let font = FontRef::new(self.font.data.data()).expect("Vello font creation error");
+        let var_loc = font.axes().location(VARIATIONS);
+        let layout = TextLayout::new(&font, &var_loc, text, alignment);
 
+        layout
+            .glyphs
+            .iter()
+            .zip(layout.advances.iter())
+            .map(|(glyph, advance)| {
+                let rect = vello::kurbo::Rect::new(
+                    glyph.x as f64,
+                    (glyph.y - layout.ascent) as f64,
+                    (glyph.x + advance) as f64,
+                    (glyph.y - layout.descent) as f64,
+                );
+                layout.alignment.transform_rect_bbox(rect)
+            })
+            .collect()
+    }
+}
+
+/// Glyphs positioned on their baselines, y down, and the affine aligning them
+/// around the text's origin.
+struct TextLayout {
+    glyphs: Vec<Glyph>,
+    advances: Vec<f32>,
+    alignment: Affine,
+    ascent: f32,
+    descent: f32,
+}
+
+impl TextLayout {
+    fn new(
+        font: &FontRef,
+        var_loc: &vello::skrifa::instance::Location,
+        text: &VelloText,
+        alignment: VelloTextAlignment,
+    ) -> Self {
         let font_size = vello::skrifa::instance::Size::new(text.size);
         let charmap = font.charmap();
-        let axes = font.axes();
-        let var_loc = axes.location(VARIATIONS);
-        let metrics = font.metrics(font_size, &var_loc);
+        let metrics = font.metrics(font_size, var_loc);
         let line_height = metrics.ascent - metrics.descent + metrics.leading;
-        let glyph_metrics = font.glyph_metrics(font_size, &var_loc);
+        let glyph_metrics = font.glyph_metrics(font_size, var_loc);
 
         let mut pen_x = 0f32;
         let mut pen_y = 0f32;
         let mut width = 0f32;
+        let mut advances = Vec::new();
         let glyphs: Vec<Glyph> = text
             .content
             .chars()
@@ -104,6 +190,7 @@ impl VelloFont {
                 let x = pen_x;
                 pen_x += advance;
                 width = width.max(pen_x);
+                advances.push(advance);
                 Some(Glyph {
                     id: gid.to_u16() as u32,
                     x,
@@ -112,7 +199,7 @@ impl VelloFont {
             })
             .collect();
         // Push up from pen_y
-        transform *= vello::kurbo::Affine::translate((0.0, -pen_y as f64));
+        let mut transform = vello::kurbo::Affine::translate((0.0, -pen_y as f64));
 
         // Alignment settings
         let width = width as f64;
@@ -147,12 +234,12 @@ impl VelloFont {
             }
         }
 
-        scene
-            .draw_glyphs(&self.font)
-            .font_size(text.size)
-            .transform(transform)
-            .normalized_coords(var_loc.coords())
-            .brush(&text.brush.clone().unwrap_or(Brush::Solid(Color::WHITE)))
-            .draw(vello::peniko::Fill::EvenOdd, glyphs.into_iter());
+        Self {
+            glyphs,
+            advances,
+            alignment: transform,
+            ascent: metrics.ascent,
+            descent: metrics.descent,
+        }
     }
 }
@@ -28,7 +28,7 @@ pub enum VelloTextAlignment {
     TopRight,
 }
 
-#[derive(Component, Default, Clone)]
+#[derive(Component, Default, Clone, PartialEq)]
 pub struct VelloText {
     pub content: String,
     pub size: f32,
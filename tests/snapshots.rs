@@ -0,0 +1,221 @@
+//! Golden-image tests of Vello content in each coordinate space, through the
+//! affines prepared for a 64x64 view.
+//!
+//! Run with `cargo test --features testing,svg,lottie --test snapshots`. Set
+//! `BEVY_VELLO_UPDATE_SNAPSHOTS` to rewrite the PNGs in `tests/snapshots`.
+#![cfg(all(feature = "testing", not(target_arch = "wasm32")))]
+
+use bevy::prelude::*;
+use bevy_vello::prelude::*;
+use bevy_vello::testing::{assert_snapshot, SnapshotApp, Tolerance};
+use std::path::PathBuf;
+
+const SIZE: UVec2 = UVec2::new(64, 64);
+const BLUE: peniko::Color = peniko::Color::rgb8(0x20, 0x60, 0xc0);
+const FONT: &[u8] = include_bytes!("../examples/text/src/assets/Rubik-Medium.ttf");
+
+fn snapshot(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(name)
+        .with_extension("png")
+}
+
+/// A scene filling `rect`, in the scene's own y down coordinates.
+fn rect_scene(rect: kurbo::Rect) -> VelloScene {
+    let mut scene = VelloScene::new();
+    scene.fill(
+        peniko::Fill::NonZero,
+        kurbo::Affine::IDENTITY,
+        BLUE,
+        None,
+        &rect,
+    );
+    scene
+}
+
+fn render(app: &mut SnapshotApp) -> Image {
+    app.render().expect("the snapshot app rendered no frame")
+}
+
+#[test]
+fn scene_world_space() {
+    let mut app = SnapshotApp::new(SIZE);
+    // The origin lands 8px left of and 8px above the center of the view
+    app.world_mut().spawn(VelloSceneBundle {
+        scene: rect_scene(kurbo::Rect::new(0.0, 0.0, 16.0, 8.0)),
+        transform: Transform::from_xyz(-8.0, 8.0, 0.0),
+        ..default()
+    });
+    let frame = render(&mut app);
+    // Drawn over x 24..40, y 24..32
+    assert_snapshot(&frame, snapshot("scene_world_space"), Tolerance::default());
+}
+
+#[test]
+fn scene_screen_space() {
+    let mut app = SnapshotApp::new(SIZE);
+    app.world_mut().spawn(VelloSceneBundle {
+        scene: rect_scene(kurbo::Rect::new(0.0, 8.0, 24.0, 20.0)),
+        coordinate_space: CoordinateSpace::ScreenSpace,
+        transform: Transform::from_xyz(8.0, 0.0, 0.0),
+        ..default()
+    });
+    let frame = render(&mut app);
+    // Drawn over x 8..32, y 8..20
+    assert_snapshot(&frame, snapshot("scene_screen_space"), Tolerance::default());
+}
+
+#[test]
+fn asset_world_space() {
+    let mut app = SnapshotApp::new(SIZE);
+    let asset =
+        app.add_asset(VelloShape::rect(16.0, 8.0).to_asset(Some(&VelloFill::new(BLUE)), None));
+    app.world_mut().spawn(VelloAssetBundle {
+        vector: asset,
+        transform: Transform::from_xyz(-8.0, 8.0, 0.0),
+        ..default()
+    });
+    let frame = render(&mut app);
+    // Centered on (24, 24), drawn over x 16..32, y 20..28
+    assert_snapshot(&frame, snapshot("asset_world_space"), Tolerance::default());
+}
+
+#[test]
+fn asset_screen_space() {
+    let mut app = SnapshotApp::new(SIZE);
+    let asset =
+        app.add_asset(VelloShape::rect(16.0, 8.0).to_asset(Some(&VelloFill::new(BLUE)), None));
+    app.world_mut().spawn(VelloAssetBundle {
+        vector: asset,
+        alignment: VelloAssetAlignment::TopLeft,
+        coordinate_space: CoordinateSpace::ScreenSpace,
+        transform: Transform::from_xyz(8.0, 16.0, 0.0),
+        ..default()
+    });
+    let frame = render(&mut app);
+    // Alignment is y up in screen space too, drawn over x 8..24, y 8..16
+    assert_snapshot(&frame, snapshot("asset_screen_space"), Tolerance::default());
+}
+
+#[cfg(feature = "svg")]
+#[test]
+fn svg_asset() {
+    let mut app = SnapshotApp::new(SIZE);
+    let svg = r##"<svg xmlns="http://www.w3.org/2000/svg" width="24" height="16">
+        <circle cx="16" cy="8" r="8" fill="#e6801a"/>
+        <rect width="16" height="16" fill="#2060c0"/>
+    </svg>"##;
+    let asset = bevy_vello::integrations::svg::load_svg_from_str(svg).expect("the SVG is valid");
+    let asset = app.add_asset(asset);
+    app.world_mut().spawn(VelloAssetBundle {
+        vector: asset,
+        transform: Transform::from_xyz(-8.0, 8.0, 0.0),
+        ..default()
+    });
+    let frame = render(&mut app);
+    // Centered on (24, 24), drawn over x 12..36, y 16..32
+    assert_snapshot(&frame, snapshot("svg_asset"), Tolerance::default());
+}
+
+#[cfg(feature = "lottie")]
+#[test]
+fn lottie_asset() {
+    let mut app = SnapshotApp::new(SIZE);
+    let asset = bevy_vello::integrations::lottie::load_lottie_from_bytes(include_bytes!(
+        "assets/badge.json"
+    ))
+    .expect("the Lottie is valid");
+    let asset = app.add_asset(asset);
+    app.world_mut().spawn(VelloAssetBundle {
+        vector: asset,
+        transform: Transform::from_xyz(-8.0, 8.0, 0.0),
+        ..default()
+    });
+    let frame = render(&mut app);
+    // The same badge as `svg_asset`, on its first frame
+    assert_snapshot(&frame, snapshot("lottie_asset"), Tolerance::default());
+}
+
+/// Text is drawn inside the bounds it's measured with, and matches its
+/// snapshot.
+fn assert_text_placed(
+    name: &str,
+    coordinate_space: CoordinateSpace,
+    transform: Transform,
+    center: Vec2,
+) {
+    let mut app = SnapshotApp::new(SIZE);
+    let font = app.add_font(FONT.to_vec());
+    let text = VelloText {
+        // Capitals only, so nothing reaches above the cap height
+        content: "VELLO".to_string(),
+        size: 12.0,
+        brush: Some(BLUE.into()),
+    };
+    let measured = app
+        .world_mut()
+        .resource::<Assets<VelloFont>>()
+        .get(&font)
+        .expect("the font was added")
+        .sizeof(&text);
+    app.world_mut().spawn(VelloTextBundle {
+        font,
+        text: text.clone(),
+        alignment: VelloTextAlignment::Center,
+        coordinate_space,
+        transform,
+        ..default()
+    });
+    let frame = render(&mut app);
+
+    // Centered on its baseline, leaving half the font size for descenders
+    let expected = Rect::new(
+        center.x - measured.x / 2.0,
+        center.y - measured.y / 2.0,
+        center.x + measured.x / 2.0,
+        center.y + measured.y / 2.0 + text.size / 2.0,
+    )
+    .inset(1.0);
+    let drawn = drawn_bounds(&frame).expect("no text was drawn");
+    assert!(
+        expected.contains(drawn.min) && expected.contains(drawn.max),
+        "text drawn over {drawn:?}, outside of {expected:?}"
+    );
+    assert_snapshot(&frame, snapshot(name), Tolerance::default());
+}
+
+/// The bounds of every pixel that isn't the white background.
+fn drawn_bounds(frame: &Image) -> Option<Rect> {
+    let width = frame.width() as usize;
+    frame
+        .data
+        .chunks_exact(4)
+        .enumerate()
+        .filter(|(_, pixel)| pixel[..3].iter().any(|channel| *channel < 0xf0))
+        .map(|(i, _)| {
+            let min = Vec2::new((i % width) as f32, (i / width) as f32);
+            Rect::from_corners(min, min + Vec2::ONE)
+        })
+        .reduce(|bounds, pixel| bounds.union(pixel))
+}
+
+#[test]
+fn text_world_space() {
+    assert_text_placed(
+        "text_world_space",
+        CoordinateSpace::WorldSpace,
+        Transform::from_xyz(0.0, 8.0, 0.0),
+        Vec2::new(32.0, 24.0),
+    );
+}
+
+#[test]
+fn text_screen_space() {
+    assert_text_placed(
+        "text_screen_space",
+        CoordinateSpace::ScreenSpace,
+        Transform::from_xyz(32.0, 40.0, 0.0),
+        Vec2::new(32.0, 40.0),
+    );
+}